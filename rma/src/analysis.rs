@@ -0,0 +1,269 @@
+//! Exact analysis of the variants a room can generate, following the selection model in
+//! [`crate::random`].
+
+use serde::Serialize;
+
+use crate::rma::{FeaturePath, RandomSelector, RoomFeature, RoomGenerator};
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct CountRange {
+    pub min: u32,
+    pub max: u32,
+    pub expected: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OutcomeAnalysis {
+    /// Number of distinct selections of features the room can generate. Saturates at
+    /// `u128::MAX`.
+    pub outcomes: u128,
+    /// Probability of each feature being generated, parents first.
+    pub inclusion: Vec<(FeaturePath, f64)>,
+    pub entrances: CountRange,
+    pub spawn_actors: CountRange,
+    pub resources: CountRange,
+}
+
+const ENTRANCES: usize = 0;
+const SPAWN_ACTORS: usize = 1;
+const RESOURCES: usize = 2;
+
+/// Per counted feature kind, indexed by the constants above.
+type Tally = [u32; 3];
+
+/// Outcomes and count bounds of a list of features that are generated together.
+struct Summary {
+    outcomes: u128,
+    min: Tally,
+    max: Tally,
+}
+
+impl RandomSelector {
+    /// Probability of generating each number of children, indexed by count.
+    pub fn count_distribution(&self) -> Vec<f64> {
+        let children = self.base.room_features.len() as i64;
+        let (min, max) = (self.min as i64, self.max as i64);
+        let mut distribution = vec![0.0; children as usize + 1];
+        let range = max - min + 1;
+        if range <= 0 {
+            distribution[min.clamp(0, children) as usize] = 1.0;
+            return distribution;
+        }
+        // counts drawn outside of 0..=children are clamped onto its ends
+        for count in min.clamp(0, children)..=max.clamp(0, children) {
+            let low = if count == 0 { min } else { count };
+            let high = if count == children { max } else { count };
+            distribution[count as usize] = (high.min(max) - low.max(min) + 1) as f64 / range as f64;
+        }
+        distribution
+    }
+}
+
+fn analyze_features(
+    features: &[RoomFeature],
+    path: &mut FeaturePath,
+    probability: f64,
+    analysis: &mut OutcomeAnalysis,
+) -> Summary {
+    let mut summary = Summary {
+        outcomes: 1,
+        min: Tally::default(),
+        max: Tally::default(),
+    };
+    path.push(0);
+    for (i, feature) in features.iter().enumerate() {
+        *path.last_mut().unwrap() = i;
+        let feature = analyze_feature(feature, path, probability, analysis);
+        summary.outcomes = summary.outcomes.saturating_mul(feature.outcomes);
+        for k in 0..3 {
+            summary.min[k] += feature.min[k];
+            summary.max[k] += feature.max[k];
+        }
+    }
+    path.pop();
+    summary
+}
+
+fn analyze_feature(
+    feature: &RoomFeature,
+    path: &mut FeaturePath,
+    probability: f64,
+    analysis: &mut OutcomeAnalysis,
+) -> Summary {
+    analysis.inclusion.push((path.clone(), probability));
+
+    let counted = match feature {
+        RoomFeature::EntranceFeature(_) => Some(ENTRANCES),
+        RoomFeature::SpawnActorFeature(_) => Some(SPAWN_ACTORS),
+        RoomFeature::ResourceFeature(_) => Some(RESOURCES),
+        _ => None,
+    };
+
+    match feature {
+        RoomFeature::RandomSelector(selector) => {
+            analyze_selector(selector, path, probability, analysis)
+        }
+        RoomFeature::FloodFillProceduralPillar
+        | RoomFeature::RandomSubRoomFeature
+        | RoomFeature::SubRoomFeature => Summary {
+            outcomes: 1,
+            min: Tally::default(),
+            max: Tally::default(),
+        },
+        _ => {
+            let mut summary =
+                analyze_features(&feature.base().room_features, path, probability, analysis);
+            if let Some(k) = counted {
+                summary.min[k] += 1;
+                summary.max[k] += 1;
+                let expected = match k {
+                    ENTRANCES => &mut analysis.entrances.expected,
+                    SPAWN_ACTORS => &mut analysis.spawn_actors.expected,
+                    _ => &mut analysis.resources.expected,
+                };
+                *expected += probability;
+            }
+            summary
+        }
+    }
+}
+
+fn analyze_selector(
+    selector: &RandomSelector,
+    path: &mut FeaturePath,
+    probability: f64,
+    analysis: &mut OutcomeAnalysis,
+) -> Summary {
+    let distribution = selector.count_distribution();
+    let children = selector.base.room_features.len();
+    let expected_count = distribution
+        .iter()
+        .enumerate()
+        .map(|(count, p)| count as f64 * p)
+        .sum::<f64>();
+    let child_probability = if children > 0 {
+        probability * expected_count / children as f64
+    } else {
+        0.0
+    };
+
+    let mut summaries = vec![];
+    path.push(0);
+    for (i, child) in selector.base.room_features.iter().enumerate() {
+        *path.last_mut().unwrap() = i;
+        summaries.push(analyze_feature(child, path, child_probability, analysis));
+    }
+    path.pop();
+
+    // elementary symmetric polynomials of the child outcome counts give the number of outcomes
+    // for each possible number of selected children
+    let mut by_count = vec![0u128; children + 1];
+    by_count[0] = 1;
+    for (i, child) in summaries.iter().enumerate() {
+        for count in (1..=i + 1).rev() {
+            by_count[count] =
+                by_count[count].saturating_add(by_count[count - 1].saturating_mul(child.outcomes));
+        }
+    }
+
+    let possible = || (0..=children).filter(|&count| distribution[count] > 0.0);
+    let mut summary = Summary {
+        outcomes: possible().fold(0u128, |acc, count| acc.saturating_add(by_count[count])),
+        min: Tally::default(),
+        max: Tally::default(),
+    };
+    for k in 0..3 {
+        let mut mins = summaries.iter().map(|s| s.min[k]).collect::<Vec<_>>();
+        let mut maxes = summaries.iter().map(|s| s.max[k]).collect::<Vec<_>>();
+        mins.sort_unstable();
+        maxes.sort_unstable_by(|a, b| b.cmp(a));
+        summary.min[k] = possible()
+            .map(|count| mins[..count].iter().sum())
+            .min()
+            .unwrap_or_default();
+        summary.max[k] = possible()
+            .map(|count| maxes[..count].iter().sum())
+            .max()
+            .unwrap_or_default();
+    }
+    summary
+}
+
+impl RoomGenerator {
+    /// Enumerate the possible outcomes of all `RandomSelector`s in the room.
+    pub fn analyze_outcomes(&self) -> OutcomeAnalysis {
+        let mut analysis = OutcomeAnalysis::default();
+        let summary = analyze_features(&self.room_features, &mut vec![], 1.0, &mut analysis);
+        analysis.outcomes = summary.outcomes;
+        for (k, range) in [
+            &mut analysis.entrances,
+            &mut analysis.spawn_actors,
+            &mut analysis.resources,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            range.min = summary.min[k];
+            range.max = summary.max[k];
+        }
+        analysis
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rma::{EntranceFeature, RoomFeatureBase};
+
+    use super::*;
+
+    #[test]
+    fn test_nested_selectors() {
+        let entrance = || RoomFeature::EntranceFeature(EntranceFeature::default());
+        let selector = |min, max, room_features| {
+            RoomFeature::RandomSelector(RandomSelector {
+                base: RoomFeatureBase { room_features },
+                min,
+                max,
+            })
+        };
+        let room = RoomGenerator {
+            room_features: vec![
+                entrance(),
+                selector(
+                    1,
+                    2,
+                    vec![entrance(), selector(0, 1, vec![entrance(), entrance()])],
+                ),
+            ],
+            ..Default::default()
+        };
+        let analysis = room.analyze_outcomes();
+        // the inner selector has 3 outcomes, so the outer one has 1 + 3 picking one child and
+        // 1 * 3 picking both
+        assert_eq!(analysis.outcomes, 7);
+        assert_eq!(analysis.entrances.min, 1);
+        assert_eq!(analysis.entrances.max, 3);
+        assert_eq!(analysis.entrances.expected, 1.0 + 0.75 + 2.0 * 0.1875);
+        assert_eq!(
+            analysis.inclusion,
+            vec![
+                (vec![0], 1.0),
+                (vec![1], 1.0),
+                (vec![1, 0], 0.75),
+                (vec![1, 1], 0.75),
+                (vec![1, 1, 0], 0.1875),
+                (vec![1, 1, 1], 0.1875),
+            ]
+        );
+        serde_json::to_string(&analysis).unwrap();
+
+        let RoomFeature::RandomSelector(clamped) = selector(-1, 4, vec![entrance(), entrance()])
+        else {
+            unreachable!()
+        };
+        assert_eq!(
+            clamped.count_distribution(),
+            vec![2.0 / 6.0, 1.0 / 6.0, 3.0 / 6.0]
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! Space taken up by a room's features, checked against the bounds the room declares.
+//!
+//! `RoomGeneratorBase::bounds` is the radius of a sphere around the room's origin. Features are
+//! measured at the largest of their ranges with noise included, so a room passing the check
+//! stays inside its bounds in every variant.
+
+use three_d_asset::{vec3, InnerSpace, Vector3};
+
+use crate::rma::{iter_features, FeaturePath, RoomFeature, RoomGenerator};
+
+/// Axis aligned box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Extent {
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>,
+}
+
+impl Extent {
+    /// Box of half size `radius` around `center`.
+    pub fn around(center: Vector3<f32>, radius: Vector3<f32>) -> Self {
+        Self {
+            min: center - radius,
+            max: center + radius,
+        }
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            min: vec3(
+                self.min.x.min(other.min.x),
+                self.min.y.min(other.min.y),
+                self.min.z.min(other.min.z),
+            ),
+            max: vec3(
+                self.max.x.max(other.max.x),
+                self.max.y.max(other.max.y),
+                self.max.z.max(other.max.z),
+            ),
+        }
+    }
+
+    pub fn size(&self) -> Vector3<f32> {
+        self.max - self.min
+    }
+
+    /// Corners indexed by the bits of their signs along X, Y and Z.
+    pub fn corners(&self) -> [Vector3<f32>; 8] {
+        [0, 1, 2, 3, 4, 5, 6, 7].map(|i| {
+            vec3(
+                if i & 1 == 0 { self.min.x } else { self.max.x },
+                if i & 2 == 0 { self.min.y } else { self.max.y },
+                if i & 4 == 0 { self.min.z } else { self.max.z },
+            )
+        })
+    }
+}
+
+/// Spheres covering a feature, points having a radius of zero.
+type Balls = Vec<(Vector3<f32>, f32)>;
+
+impl RoomFeature {
+    /// Spheres covering everything the feature can affect, not including its children.
+    fn balls(&self) -> Balls {
+        match self {
+            RoomFeature::FloodFillLine(line) => line
+                .points
+                .iter()
+                .flat_map(|p| {
+                    let (lo, hi) = p.profile().bounds();
+                    let location = Vector3::from(p.location);
+                    let extent = Extent {
+                        min: location + lo,
+                        max: location + hi,
+                    };
+                    extent.corners().map(|c| (c, 0.0))
+                })
+                .collect(),
+            RoomFeature::FloodFillPillar(pillar) => (0..pillar.points.len())
+                .map(|i| {
+                    let radius = pillar.radius_range(i).1 + pillar.max_noise(i);
+                    (pillar.points[i].location.into(), radius)
+                })
+                .collect(),
+            RoomFeature::FloodFillBox(b) => b.corners(b.noise_range.abs()).map(|c| (c, 0.0)).into(),
+            RoomFeature::SpawnTriggerFeature(f) => f.corners().map(|c| (c, 0.0)).into(),
+            _ => self
+                .location()
+                .map(|l| (l.into(), 0.0))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Box around everything the feature can affect, not including its children.
+    pub fn extent(&self) -> Option<Extent> {
+        self.balls()
+            .into_iter()
+            .map(|(c, r)| Extent::around(c, vec3(r, r, r)))
+            .reduce(Extent::union)
+    }
+
+    /// Farthest the feature reaches from the room's origin, not including its children.
+    pub fn reach(&self) -> Option<f32> {
+        self.balls()
+            .into_iter()
+            .map(|(c, r)| c.magnitude() + r)
+            .reduce(f32::max)
+    }
+}
+
+/// A feature reaching outside the room's bounds.
+#[derive(Debug, Clone)]
+pub struct OutOfBounds {
+    pub path: FeaturePath,
+    /// Distance from the origin of the feature's farthest point.
+    pub reach: f32,
+    pub extent: Extent,
+}
+
+#[derive(Debug, Clone)]
+pub struct BoundsReport {
+    /// Radius the room declares.
+    pub declared: f32,
+    /// Box around every feature, if there are any with a position.
+    pub extent: Option<Extent>,
+    /// Farthest any feature reaches from the origin.
+    pub reach: f32,
+    /// Features reaching outside the declared radius, parents first.
+    pub outside: Vec<OutOfBounds>,
+}
+
+impl RoomGenerator {
+    /// Box around every feature in the room, including all children of random selectors.
+    pub fn extent(&self) -> Option<Extent> {
+        let mut extent: Option<Extent> = None;
+        iter_features(&self.room_features, &mut vec![], &mut |feature, _| {
+            if let Some(e) = feature.extent() {
+                extent = Some(extent.map_or(e, |x| x.union(e)));
+            }
+        });
+        extent
+    }
+
+    /// Compare the space taken up by every feature against the declared bounds.
+    pub fn check_bounds(&self) -> BoundsReport {
+        let declared = self.base.bounds;
+        let mut reach = 0.0f32;
+        let mut outside = vec![];
+        iter_features(&self.room_features, &mut vec![], &mut |feature, path| {
+            let (Some(r), Some(extent)) = (feature.reach(), feature.extent()) else {
+                return;
+            };
+            reach = reach.max(r);
+            if r > declared {
+                outside.push(OutOfBounds {
+                    path: path.to_vec(),
+                    reach: r,
+                    extent,
+                });
+            }
+        });
+        BoundsReport {
+            declared,
+            extent: self.extent(),
+            reach,
+            outside,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rma::{FRandLinePoint, FRandRange, FVector, FloodFillPillar, RoomGeneratorBase};
+
+    use super::*;
+
+    #[test]
+    fn test_check_bounds() {
+        let point = |x| FRandLinePoint {
+            location: FVector { x, y: 0.0, z: 0.0 },
+            range: FRandRange {
+                min: 100.0,
+                max: 300.0,
+            },
+            ..Default::default()
+        };
+        let room = RoomGenerator {
+            base: RoomGeneratorBase {
+                bounds: 1000.0,
+                ..Default::default()
+            },
+            room_features: vec![
+                RoomFeature::FloodFillPillar(FloodFillPillar {
+                    points: vec![point(0.0), point(500.0)],
+                    ..Default::default()
+                }),
+                RoomFeature::FloodFillPillar(FloodFillPillar {
+                    points: vec![point(-500.0), point(-800.0)],
+                    ..Default::default()
+                }),
+            ],
+        };
+        let report = room.check_bounds();
+        assert_eq!(report.reach, 1100.0);
+        assert_eq!(report.outside.len(), 1);
+        assert_eq!(report.outside[0].path, vec![1]);
+        let extent = report.extent.unwrap();
+        assert_eq!(extent.min, vec3(-1100.0, -300.0, -300.0));
+        assert_eq!(extent.max, vec3(800.0, 300.0, 300.0));
+    }
+}
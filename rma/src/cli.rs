@@ -0,0 +1,412 @@
+//! Headless subcommands of the `rma` binary. Arguments not starting with a known subcommand are
+//! treated as a path to open in the editor.
+
+use std::{collections::HashMap, fs, io::Write, path::Path, str::FromStr};
+
+use anyhow::{bail, ensure, Context, Result};
+use three_d_asset::{vec2, vec3, Vector3};
+use unreal_asset::engine_version::EngineVersion;
+
+use crate::{
+    export,
+    lint::{self, Severity},
+    lint_config::{Config, Suppressions},
+    mesh::SurfaceMesh,
+    raster::{Camera, RenderOptions},
+    read_asset, read_rma,
+    rma::{format_path, RoomGenerator},
+    slice::{self, Slice, SlicePlane},
+    svg::{SvgOptions, View},
+    voxel::{CarveSettings, DensityGrid},
+};
+
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self> {
+        let mut positional = vec![];
+        let mut options = HashMap::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if let Some(name) = arg.strip_prefix("--") {
+                let value = iter
+                    .next()
+                    .with_context(|| format!("missing value for --{name}"))?;
+                options.insert(name.to_string(), value.clone());
+            } else {
+                positional.push(arg.clone());
+            }
+        }
+        Ok(Self {
+            positional,
+            options,
+        })
+    }
+    fn positional(&self, index: usize, name: &str) -> Result<&str> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .with_context(|| format!("expected <{name}> argument"))
+    }
+    fn option<T: FromStr>(&self, name: &str, default: T) -> Result<T>
+    where
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        match self.options.get(name) {
+            Some(value) => value
+                .parse()
+                .with_context(|| format!("invalid value for --{name}: {value:?}")),
+            None => Ok(default),
+        }
+    }
+    /// Number that must be greater than zero, such as a size.
+    fn positive(&self, name: &str, default: f32) -> Result<f32> {
+        let value = self.option(name, default)?;
+        ensure!(
+            value > 0.0,
+            "invalid value for --{name}: expected a positive number"
+        );
+        Ok(value)
+    }
+    /// `N` comma separated numbers.
+    fn numbers<const N: usize>(&self, name: &str, expected: &str) -> Result<Option<[f32; N]>> {
+        let Some(value) = self.options.get(name) else {
+            return Ok(None);
+        };
+        let parts = value
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<f32>, _>>()
+            .ok()
+            .and_then(|parts| parts.try_into().ok())
+            .with_context(|| format!("invalid value for --{name}: expected {expected}"))?;
+        Ok(Some(parts))
+    }
+    /// Comma separated `x,y,z` vector.
+    fn vector(&self, name: &str) -> Result<Option<Vector3<f32>>> {
+        Ok(self.numbers(name, "x,y,z")?.map(Vector3::from))
+    }
+    /// Writer for `--output`, defaulting to stdout.
+    fn output(&self) -> Result<Box<dyn Write>> {
+        Ok(match self.options.get("output") {
+            Some(path) => Box::new(fs::File::create(path)?),
+            None => Box::new(std::io::stdout().lock()),
+        })
+    }
+}
+
+fn load(path: &str) -> Result<RoomGenerator> {
+    read_rma(read_asset(path, EngineVersion::VER_UE4_27)?)
+        .with_context(|| format!("parsing asset {path:?}"))
+}
+
+/// Run the subcommand named by the first argument. Returns `false` if there is none.
+pub fn run(args: &[String]) -> Result<bool> {
+    let Some((command, rest)) = args.split_first() else {
+        return Ok(false);
+    };
+    let command: fn(&Args) -> Result<()> = match command.as_str() {
+        "stats" => stats,
+        "export" => export,
+        "import" => import,
+        "svg" => svg,
+        "render" => render,
+        "thumbnails" => thumbnails,
+        "slice" => slice,
+        "lint" => lint,
+        // anything else is left to the viewer, flags included
+        _ => return Ok(false),
+    };
+    command(&Args::parse(rest)?)?;
+    Ok(true)
+}
+
+/// `stats <path> [--seeds N] [--first-seed S] [--format csv|json] [--summary true|false]
+/// [--output FILE]`
+///
+/// With `--summary true` the CSV has one row per measurement with its distribution instead of
+/// one row per seed.
+fn stats(args: &Args) -> Result<()> {
+    let rma = load(args.positional(0, "path")?)?;
+    let stats = rma.statistics(args.option("first-seed", 0)?, args.option("seeds", 1000)?);
+    let output = args.output()?;
+    match args.option("format", "csv".to_string())?.as_str() {
+        "csv" if args.option("summary", false)? => stats.write_summary_csv(output),
+        "csv" => stats.write_csv(output),
+        "json" => stats.write_json(output),
+        format => bail!("unknown format {format:?}"),
+    }
+}
+
+/// `export <path> <output.obj|stl|ply|glb> [--seed S] [--cave true|false] [--smooth N]
+/// [--voxel-size N]`
+fn export(args: &Args) -> Result<()> {
+    let mut rma = load(args.positional(0, "path")?)?;
+    let output = args.positional(1, "output")?;
+    if args.options.contains_key("seed") {
+        rma = rma.instantiate_sampled(args.option("seed", 0)?);
+    }
+    let cave = if args.option("cave", true)? {
+        let settings = CarveSettings {
+            voxel_size: args.positive("voxel-size", CarveSettings::default().voxel_size)?,
+            ..Default::default()
+        };
+        Some(SurfaceMesh::carve(
+            &rma,
+            &settings,
+            args.option("smooth", 2)?,
+        ))
+    } else {
+        None
+    };
+    export::save(&rma, cave.as_ref(), Path::new(output))
+}
+
+/// `import <path> <edited.glb|gltf> [--output FILE]`
+///
+/// Applies edits made to a glTF written by `export` and writes the updated room as JSON.
+fn import(args: &Args) -> Result<()> {
+    let mut rma = load(args.positional(0, "path")?)?;
+    let input = args.positional(1, "gltf")?;
+    let data = fs::read(input).with_context(|| format!("reading {input:?}"))?;
+    let report = rma.apply_gltf(&data)?;
+    for (path, point) in &report.updated {
+        match point {
+            Some(point) => eprintln!("updated {} point {point}", format_path(path)),
+            None => eprintln!("updated {}", format_path(path)),
+        }
+    }
+    for name in &report.unmatched {
+        eprintln!("unmatched node {name:?}");
+    }
+    for name in &report.added {
+        eprintln!("added node {name:?}");
+    }
+    serde_json::to_writer_pretty(args.output()?, &rma)?;
+    Ok(())
+}
+
+/// `svg <path> [--views top,front,side] [--scale PX_PER_UNIT] [--seed S] [--output FILE]`
+fn svg(args: &Args) -> Result<()> {
+    let path = args.positional(0, "path")?;
+    let mut rma = load(path)?;
+    if args.options.contains_key("seed") {
+        rma = rma.instantiate_sampled(args.option("seed", 0)?);
+    }
+    let views = args
+        .option("views", "top".to_string())?
+        .split(',')
+        .map(|view| {
+            Ok(match view {
+                "top" => View::Top,
+                "front" => View::Front,
+                "side" => View::Side,
+                _ => bail!("unknown view {view:?}, expected top, front or side"),
+            })
+        })
+        .collect::<Result<_>>()?;
+    let options = SvgOptions {
+        views,
+        scale: args.option("scale", SvgOptions::default().scale)?,
+        title: Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned()),
+    };
+    rma.write_svg(&options, args.output()?)
+}
+
+/// Shared options of `render` and `thumbnails`.
+fn render_options(args: &Args, rma: &RoomGenerator) -> Result<RenderOptions> {
+    let defaults = RenderOptions::default();
+    let fov = args.option("fov", defaults.fov)?;
+    let camera = match (args.vector("camera")?, args.vector("target")?) {
+        (Some(position), target) => Some(Camera {
+            position,
+            target: target.unwrap_or(vec3(0.0, 0.0, 0.0)),
+            fov,
+        }),
+        (None, Some(_)) => bail!("--target requires --camera"),
+        (None, None) => None,
+    };
+    let cave = args
+        .option("cave", true)?
+        .then(|| SurfaceMesh::carve(rma, &CarveSettings::default(), 2));
+    Ok(RenderOptions {
+        width: args.option("width", defaults.width)?,
+        height: args.option("height", defaults.height)?,
+        camera,
+        fov,
+        cave,
+    })
+}
+
+/// `render <path> --output FILE.png [--width W] [--height H] [--camera x,y,z] [--target x,y,z]
+/// [--fov DEG] [--cave true|false] [--seed S]`
+fn render(args: &Args) -> Result<()> {
+    let mut rma = load(args.positional(0, "path")?)?;
+    if args.options.contains_key("seed") {
+        rma = rma.instantiate_sampled(args.option("seed", 0)?);
+    }
+    let options = render_options(args, &rma)?;
+    rma.render(&options).write_png(args.output()?)
+}
+
+/// `thumbnails <dir> [--output-dir DIR] [--width W] [--height H] [--fov DEG]
+/// [--cave true|false]`
+///
+/// Renders every `.uasset` in `dir` to a PNG of the same name, continuing past failures.
+fn thumbnails(args: &Args) -> Result<()> {
+    let dir = Path::new(args.positional(0, "dir")?);
+    let output_dir = args.options.get("output-dir").map(Path::new).unwrap_or(dir);
+    fs::create_dir_all(output_dir)?;
+
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?;
+    paths.retain(|p| p.extension().is_some_and(|e| e == "uasset"));
+    paths.sort();
+
+    let mut failed = 0;
+    for path in &paths {
+        let output = output_dir.join(path.with_extension("png").file_name().unwrap());
+        let result = load(&path.to_string_lossy()).and_then(|rma| {
+            let options = render_options(args, &rma)?;
+            rma.render(&options)
+                .write_png(std::io::BufWriter::new(fs::File::create(&output)?))
+        });
+        match result {
+            Ok(()) => eprintln!("{}", output.display()),
+            Err(e) => {
+                failed += 1;
+                eprintln!("{}: {e:#}", path.display());
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{failed} of {} assets failed", paths.len());
+    }
+    Ok(())
+}
+
+/// `slice <path> (--z Z | --plane x,y,yaw) [--resolution UNITS_PER_PX] [--voxel-size N]
+/// [--seed S] [--at x,y] --output FILE.png`
+///
+/// With `--at`, the open spans of the column at that point are printed as well.
+fn slice(args: &Args) -> Result<()> {
+    let mut rma = load(args.positional(0, "path")?)?;
+    if args.options.contains_key("seed") {
+        rma = rma.instantiate_sampled(args.option("seed", 0)?);
+    }
+    let plane = match (
+        args.options.contains_key("z"),
+        args.numbers("plane", "x,y,yaw")?,
+    ) {
+        (true, None) => SlicePlane::Horizontal {
+            z: args.option("z", 0.0)?,
+        },
+        (false, Some([x, y, yaw])) => SlicePlane::Vertical {
+            origin: vec2(x, y),
+            yaw,
+        },
+        _ => bail!("expected one of --z or --plane"),
+    };
+    let settings = CarveSettings {
+        voxel_size: args.positive("voxel-size", CarveSettings::default().voxel_size)?,
+        ..Default::default()
+    };
+    let grid = DensityGrid::carve(&rma, &settings);
+
+    if let Some([x, y]) = args.numbers("at", "x,y")? {
+        for span in grid.clearance(x, y) {
+            eprintln!(
+                "open from {:.0} to {:.0} ({:.0} clearance)",
+                span.start,
+                span.end,
+                span.end - span.start
+            );
+        }
+    }
+
+    let slice = Slice::new(&grid, plane, args.positive("resolution", 10.0)?);
+    slice
+        .image(&slice::crossings(&rma, plane))
+        .write_png(args.output()?)
+}
+
+/// `lint <path>... [--config rma-lint.toml] [--format text|json] [--output FILE]`
+///
+/// Without `--config`, each asset uses the nearest `rma-lint.toml` above it. Diagnostics
+/// suppressed in an asset's `.lint.toml` sidecar are left out. Assets that cannot be read are
+/// reported and skipped. Fails if any asset has an error or could not be linted.
+fn lint(args: &Args) -> Result<()> {
+    if args.positional.is_empty() {
+        bail!("expected <path> argument");
+    }
+    let format = args.option("format", "text".to_string())?;
+    let mut output = args.output()?;
+    let mut results = vec![];
+    let (mut errors, mut failed) = (0, 0);
+    let config = args
+        .options
+        .get("config")
+        .map(|path| Config::load(Path::new(path)))
+        .transpose()?;
+    for path in &args.positional {
+        let asset = Path::new(path);
+        let result = load(path).and_then(|rma| {
+            let config = match &config {
+                Some(config) => config.clone(),
+                None => Config::discover(asset)?,
+            };
+            let name = asset.file_stem().map(|s| s.to_string_lossy());
+            let mut diagnostics = lint::check_with(&rma, name.as_deref(), &config);
+            let suppressed = Suppressions::load(asset)?.apply(&mut diagnostics);
+            Ok((diagnostics, suppressed))
+        });
+        let (diagnostics, suppressed) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                failed += 1;
+                eprintln!("{path}: {e:#}");
+                continue;
+            }
+        };
+        errors += diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .count();
+        match format.as_str() {
+            "text" => {
+                for diagnostic in &diagnostics {
+                    writeln!(output, "{path}: {diagnostic}")?;
+                }
+                if suppressed > 0 {
+                    writeln!(output, "{path}: {suppressed} suppressed")?;
+                }
+            }
+            "json" => results.push(serde_json::json!({
+                "path": path,
+                "diagnostics": diagnostics,
+                "suppressed": suppressed,
+            })),
+            format => bail!("unknown format {format:?}"),
+        }
+    }
+    if format == "json" {
+        serde_json::to_writer_pretty(&mut output, &results)?;
+        writeln!(output)?;
+    }
+    match (errors, failed) {
+        (0, 0) => {}
+        (errors, 0) => bail!("{errors} errors"),
+        (0, failed) => bail!("{failed} of {} assets failed", args.positional.len()),
+        (errors, failed) => bail!(
+            "{errors} errors, {failed} of {} assets failed",
+            args.positional.len()
+        ),
+    }
+    Ok(())
+}
@@ -0,0 +1,396 @@
+//! Export of feature outlines, point feature markers and the carved cave surface to OBJ, binary
+//! STL and PLY. glTF export lives in [`crate::gltf`].
+//!
+//! Coordinates are in UE units (centimeters) with Y negated, turning UE's left handed Z up space
+//! into the right handed Z up space most modelling tools expect.
+
+use std::{fs, io::Write, path::Path};
+
+use anyhow::{bail, Context, Result};
+use three_d_asset::{vec3, InnerSpace, Vector3};
+
+use crate::{
+    gltf,
+    mesh::SurfaceMesh,
+    rma::{format_path, iter_features, RoomFeature, RoomGenerator},
+    wireframe::{Segment, Wireframe},
+};
+
+/// Radius of the octahedron placed at point features.
+const MARKER_SIZE: f32 = 50.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Obj,
+    Stl,
+    Ply,
+    Glb,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 4] = [
+        ExportFormat::Obj,
+        ExportFormat::Stl,
+        ExportFormat::Ply,
+        ExportFormat::Glb,
+    ];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Obj => "obj",
+            ExportFormat::Stl => "stl",
+            ExportFormat::Ply => "ply",
+            ExportFormat::Glb => "glb",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        Self::ALL.into_iter().find(|f| f.extension() == extension)
+    }
+}
+
+/// Geometry of a single feature, or of the cave surface.
+#[derive(Debug, Clone)]
+pub struct ExportGroup {
+    /// Feature path and type, e.g. `0.2.1_FloodFillLine`, or `cave`.
+    pub name: String,
+    pub segments: Vec<Segment>,
+    pub mesh: SurfaceMesh,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RoomExport {
+    pub groups: Vec<ExportGroup>,
+}
+
+/// Octahedron marking a point feature.
+pub(crate) fn marker(center: Vector3<f32>) -> SurfaceMesh {
+    let directions = [
+        vec3(1.0, 0.0, 0.0),
+        vec3(-1.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        vec3(0.0, -1.0, 0.0),
+        vec3(0.0, 0.0, 1.0),
+        vec3(0.0, 0.0, -1.0),
+    ];
+    SurfaceMesh {
+        positions: directions
+            .iter()
+            .map(|&d| center + d * MARKER_SIZE)
+            .collect(),
+        normals: directions.to_vec(),
+        #[rustfmt::skip]
+        indices: vec![
+            0, 2, 4, 2, 1, 4, 1, 3, 4, 3, 0, 4,
+            2, 0, 5, 1, 2, 5, 3, 1, 5, 0, 3, 5,
+        ],
+    }
+}
+
+/// Convert from UE's left handed space.
+fn flip(v: Vector3<f32>) -> Vector3<f32> {
+    vec3(v.x, -v.y, v.z)
+}
+
+/// Triangles of `mesh` in flipped space, wound counter-clockwise.
+fn triangles(mesh: &SurfaceMesh) -> impl Iterator<Item = [u32; 3]> + '_ {
+    mesh.indices
+        .chunks_exact(3)
+        .map(|tri| [tri[0], tri[2], tri[1]])
+}
+
+/// Write `room` and optionally its cave surface in the format implied by the extension of `path`.
+pub fn save(room: &RoomGenerator, cave: Option<&SurfaceMesh>, path: &Path) -> Result<()> {
+    let Some(format) = ExportFormat::from_path(path) else {
+        bail!("unknown export format for {path:?}, expected .obj, .stl, .ply or .glb");
+    };
+    let file = fs::File::create(path).with_context(|| format!("creating {path:?}"))?;
+    let w = std::io::BufWriter::new(file);
+    match format {
+        ExportFormat::Glb => gltf::write_glb(room, cave, w),
+        format => RoomExport::new(room, cave).write(format, w),
+    }
+}
+
+impl RoomExport {
+    /// Collect the outlines of flood fill features, a marker for every point feature and
+    /// optionally the cave surface.
+    pub fn new(room: &RoomGenerator, cave: Option<&SurfaceMesh>) -> Self {
+        let mut groups = vec![];
+        iter_features(&room.room_features, &mut vec![], &mut |feature, path| {
+            let segments = match feature {
+                RoomFeature::FloodFillBox(f) => f.wireframe(),
+                RoomFeature::FloodFillPillar(f) => f.wireframe(),
+                RoomFeature::FloodFillLine(f) => f.wireframe(),
+                _ => vec![],
+            };
+            let mesh = feature
+                .location()
+                .map(|l| marker(l.into()))
+                .unwrap_or_default();
+            if !segments.is_empty() || !mesh.indices.is_empty() {
+                groups.push(ExportGroup {
+                    name: format!("{}_{}", format_path(path), feature.name().trim()),
+                    segments,
+                    mesh,
+                });
+            }
+        });
+        if let Some(cave) = cave {
+            groups.push(ExportGroup {
+                name: "cave".to_string(),
+                segments: vec![],
+                mesh: cave.clone(),
+            });
+        }
+        Self { groups }
+    }
+
+    pub fn write<W: Write>(&self, format: ExportFormat, w: W) -> Result<()> {
+        match format {
+            ExportFormat::Obj => self.write_obj(w),
+            ExportFormat::Stl => self.write_stl(w),
+            ExportFormat::Ply => self.write_ply(w),
+            ExportFormat::Glb => bail!("glTF is written from the feature tree, use `export::save`"),
+        }
+    }
+
+    /// Wavefront OBJ with one group per feature. Outlines are written as line elements.
+    pub fn write_obj<W: Write>(&self, mut w: W) -> Result<()> {
+        writeln!(w, "# exported by rma-editor")?;
+        let (mut vertices, mut normals) = (1, 1);
+        for group in &self.groups {
+            writeln!(w, "g {}", group.name)?;
+            for p in group.mesh.positions.iter().map(|&p| flip(p)) {
+                writeln!(w, "v {} {} {}", p.x, p.y, p.z)?;
+            }
+            for n in group.mesh.normals.iter().map(|&n| flip(n)) {
+                writeln!(w, "vn {} {} {}", n.x, n.y, n.z)?;
+            }
+            for tri in triangles(&group.mesh) {
+                let [a, b, c] = tri.map(|i| (vertices + i, normals + i));
+                writeln!(w, "f {}//{} {}//{} {}//{}", a.0, a.1, b.0, b.1, c.0, c.1)?;
+            }
+            vertices += group.mesh.positions.len() as u32;
+            normals += group.mesh.normals.len() as u32;
+
+            for [a, b] in &group.segments {
+                let (a, b) = (flip(*a), flip(*b));
+                writeln!(w, "v {} {} {}", a.x, a.y, a.z)?;
+                writeln!(w, "v {} {} {}", b.x, b.y, b.z)?;
+                writeln!(w, "l {} {}", vertices, vertices + 1)?;
+                vertices += 2;
+            }
+        }
+        Ok(())
+    }
+
+    /// Binary STL. STL only holds triangles so outlines are left out.
+    pub fn write_stl<W: Write>(&self, mut w: W) -> Result<()> {
+        let count = self
+            .groups
+            .iter()
+            .map(|g| g.mesh.indices.len() / 3)
+            .sum::<usize>();
+        let mut header = [0; 80];
+        let name = b"exported by rma-editor";
+        header[..name.len()].copy_from_slice(name);
+        w.write_all(&header)?;
+        w.write_all(&u32::try_from(count)?.to_le_bytes())?;
+        for group in &self.groups {
+            for tri in triangles(&group.mesh) {
+                let [a, b, c] = tri.map(|i| flip(group.mesh.positions[i as usize]));
+                let normal = (b - a).cross(c - a);
+                let normal = if normal.magnitude2() > 0.0 {
+                    normal.normalize()
+                } else {
+                    normal
+                };
+                for v in [normal, a, b, c] {
+                    for f in [v.x, v.y, v.z] {
+                        w.write_all(&f.to_le_bytes())?;
+                    }
+                }
+                w.write_all(&[0, 0])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// ASCII PLY with faces for meshes and edges for outlines. Groups are not preserved.
+    pub fn write_ply<W: Write>(&self, mut w: W) -> Result<()> {
+        let vertices = self
+            .groups
+            .iter()
+            .map(|g| g.mesh.positions.len() + g.segments.len() * 2)
+            .sum::<usize>();
+        let faces = self
+            .groups
+            .iter()
+            .map(|g| g.mesh.indices.len() / 3)
+            .sum::<usize>();
+        let edges = self.groups.iter().map(|g| g.segments.len()).sum::<usize>();
+
+        writeln!(w, "ply")?;
+        writeln!(w, "format ascii 1.0")?;
+        writeln!(w, "comment exported by rma-editor")?;
+        writeln!(w, "element vertex {vertices}")?;
+        for property in ["x", "y", "z", "nx", "ny", "nz"] {
+            writeln!(w, "property float {property}")?;
+        }
+        writeln!(w, "element face {faces}")?;
+        writeln!(w, "property list uchar int vertex_indices")?;
+        writeln!(w, "element edge {edges}")?;
+        writeln!(w, "property int vertex1")?;
+        writeln!(w, "property int vertex2")?;
+        writeln!(w, "end_header")?;
+
+        for group in &self.groups {
+            for (p, n) in group.mesh.positions.iter().zip(&group.mesh.normals) {
+                let (p, n) = (flip(*p), flip(*n));
+                writeln!(w, "{} {} {} {} {} {}", p.x, p.y, p.z, n.x, n.y, n.z)?;
+            }
+            for p in group.segments.iter().flatten().map(|&p| flip(p)) {
+                writeln!(w, "{} {} {} 0 0 0", p.x, p.y, p.z)?;
+            }
+        }
+        let mut offset = 0;
+        for group in &self.groups {
+            for [a, b, c] in triangles(&group.mesh) {
+                writeln!(w, "3 {} {} {}", offset + a, offset + b, offset + c)?;
+            }
+            offset += (group.mesh.positions.len() + group.segments.len() * 2) as u32;
+        }
+        let mut offset = 0;
+        for group in &self.groups {
+            let first = offset + group.mesh.positions.len() as u32;
+            for i in 0..group.segments.len() as u32 {
+                writeln!(w, "{} {}", first + 2 * i, first + 2 * i + 1)?;
+            }
+            offset = first + group.segments.len() as u32 * 2;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn export() -> RoomExport {
+        RoomExport {
+            groups: vec![ExportGroup {
+                name: "0_EntranceFeature".to_string(),
+                segments: vec![[vec3(0.0, 100.0, 0.0), vec3(200.0, 100.0, 50.0)]],
+                mesh: marker(vec3(0.0, 0.0, 0.0)),
+            }],
+        }
+    }
+
+    fn numbers<T: std::str::FromStr>(fields: &[&str]) -> Vec<T> {
+        fields.iter().filter_map(|f| f.parse().ok()).collect()
+    }
+
+    #[test]
+    fn test_stl() -> Result<()> {
+        let mut bytes = vec![];
+        export().write_stl(&mut bytes)?;
+        assert!(bytes.starts_with(b"exported by rma-editor"));
+        assert_eq!(&bytes[80..84], &8u32.to_le_bytes());
+        assert_eq!(bytes.len(), 84 + 8 * 50);
+
+        for record in bytes[84..].chunks_exact(50) {
+            let floats = record[..48]
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                .collect::<Vec<_>>();
+            let normal = vec3(floats[0], floats[1], floats[2]);
+            let centroid = (3..12)
+                .step_by(3)
+                .map(|i| vec3(floats[i], floats[i + 1], floats[i + 2]))
+                .fold(vec3(0.0, 0.0, 0.0), |a, p| a + p);
+            // the octahedron is centered on the origin, so faces point away from it
+            assert!((normal.magnitude() - 1.0).abs() < 1e-5);
+            assert!(normal.dot(centroid) > 0.0);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_obj_round_trip() -> Result<()> {
+        let export = export();
+        let mut bytes = vec![];
+        export.write_obj(&mut bytes)?;
+
+        let (mut positions, mut faces, mut lines) = (vec![], vec![], vec![]);
+        for line in String::from_utf8(bytes)?.lines() {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            match fields[0] {
+                "g" => assert_eq!(fields[1], "0_EntranceFeature"),
+                "v" => positions.push(flip(Vector3::from(
+                    <[f32; 3]>::try_from(numbers(&fields[1..])).unwrap(),
+                ))),
+                "f" => faces.push(
+                    fields[1..]
+                        .iter()
+                        .map(|f| f.split("//").next().unwrap().parse::<u32>().unwrap() - 1)
+                        .collect::<Vec<_>>(),
+                ),
+                "l" => lines.push(numbers::<u32>(&fields[1..])),
+                _ => {}
+            }
+        }
+
+        let mesh = &export.groups[0].mesh;
+        assert_eq!(positions[..mesh.positions.len()], mesh.positions[..]);
+        let indices = faces
+            .iter()
+            .flat_map(|f| [f[0], f[2], f[1]])
+            .collect::<Vec<_>>();
+        assert_eq!(indices, mesh.indices);
+        assert_eq!(lines, [[7, 8]]);
+        assert_eq!(positions[6..], export.groups[0].segments[0][..]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ply_round_trip() -> Result<()> {
+        let export = export();
+        let mut bytes = vec![];
+        export.write_ply(&mut bytes)?;
+        let text = String::from_utf8(bytes)?;
+        let (header, body) = text.split_once("end_header\n").unwrap();
+        assert!(header.contains("element vertex 8\n"));
+        assert!(header.contains("element face 8\n"));
+        assert!(header.contains("element edge 1\n"));
+
+        let rows = body
+            .lines()
+            .map(|l| l.split_whitespace().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let positions = rows[..8]
+            .iter()
+            .map(|r| {
+                let v = numbers::<f32>(r);
+                flip(vec3(v[0], v[1], v[2]))
+            })
+            .collect::<Vec<_>>();
+        let indices = rows[8..16]
+            .iter()
+            .flat_map(|r| {
+                let v = numbers::<u32>(r);
+                assert_eq!(v[0], 3);
+                [v[1], v[3], v[2]]
+            })
+            .collect::<Vec<_>>();
+
+        let group = &export.groups[0];
+        assert_eq!(positions[..6], group.mesh.positions[..]);
+        assert_eq!(positions[6..], group.segments[0][..]);
+        assert_eq!(indices, group.mesh.indices);
+        assert_eq!(numbers::<u32>(&rows[16]), [6, 7]);
+        assert_eq!(rows.len(), 17);
+        Ok(())
+    }
+}
@@ -0,0 +1,737 @@
+//! glTF 2.0 (binary `.glb`) export of a room.
+//!
+//! Every feature becomes a node named after its type and path, nested like the feature tree, with
+//! its path and parameters in `extras`. Point features are empties placed and oriented like the
+//! feature, line and pillar points are child empties, and outlines and the cave surface are
+//! meshes.
+//!
+//! glTF is right handed, Y up and in meters: UE's `(x, y, z)` maps to `(x, z, y) / 100`.
+
+use std::io::Write;
+
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use three_d_asset::{vec3, InnerSpace, Mat3, Mat4, Quat, Vector3};
+
+use crate::{
+    mesh::SurfaceMesh,
+    rma::{format_path, FQuat, FRotator, FVector, FeaturePath, RoomFeature, RoomGenerator},
+    wireframe::{Segment, Wireframe},
+};
+
+/// Meters per UE unit.
+pub const SCALE: f32 = 0.01;
+
+/// Name of the `extras` key holding the feature path of a node.
+pub const PATH_KEY: &str = "rma_path";
+/// Name of the `extras` key holding the index of a line or pillar point.
+pub const POINT_KEY: &str = "rma_point";
+/// Name of the `extras` key marking the root node, holding the room's own parameters.
+pub const ROOM_KEY: &str = "rma_room";
+/// Name of the `extras` key marking the cave surface node.
+pub const CAVE_KEY: &str = "rma_cave";
+
+pub fn to_gltf(v: Vector3<f32>) -> Vector3<f32> {
+    vec3(v.x, v.z, v.y) * SCALE
+}
+
+pub fn from_gltf(v: Vector3<f32>) -> Vector3<f32> {
+    vec3(v.x, v.z, v.y) / SCALE
+}
+
+/// Convert a rotation between UE and glTF space. The mapping is a reflection so the conversion
+/// is its own inverse.
+pub fn convert_rotation(q: Quat) -> Quat {
+    Quat::new(q.s, -q.v.x, -q.v.z, -q.v.y)
+}
+
+/// Rigid transform in glTF space.
+#[derive(Debug, Clone, Copy)]
+pub struct Transform {
+    pub translation: Vector3<f32>,
+    pub rotation: Quat,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        translation: vec3(0.0, 0.0, 0.0),
+        rotation: Quat::new(1.0, 0.0, 0.0, 0.0),
+    };
+
+    /// This transform expressed in the space of `parent`.
+    pub fn relative_to(self, parent: Transform) -> Transform {
+        let inverse = parent.rotation.conjugate();
+        Transform {
+            translation: inverse * (self.translation - parent.translation),
+            rotation: inverse * self.rotation,
+        }
+    }
+
+    fn inverse_apply(self, v: Vector3<f32>) -> Vector3<f32> {
+        self.rotation.conjugate() * (v - self.translation)
+    }
+}
+
+/// World transform of the node for `feature`, if it is placed on its own rather than following
+/// its parent.
+fn feature_transform(feature: &RoomFeature) -> Option<Transform> {
+    let at = |location: Vector3<f32>, rotation: Quat| Transform {
+        translation: to_gltf(location),
+        rotation: convert_rotation(rotation),
+    };
+    let identity = Transform::IDENTITY.rotation;
+    Some(match feature {
+        RoomFeature::FloodFillBox(f) => at(f.position.into(), f.rotation.into()),
+        RoomFeature::EntranceFeature(f) => at(f.location.into(), f.direction.into()),
+        RoomFeature::SpawnTriggerFeature(f) => {
+            at(f.transform.translation.into(), f.transform.rotation.into())
+        }
+        _ => at(feature.location()?.into(), identity),
+    })
+}
+
+#[derive(Default)]
+struct Builder {
+    nodes: Vec<Value>,
+    meshes: Vec<Value>,
+    accessors: Vec<Value>,
+    buffer_views: Vec<Value>,
+    buffer: Vec<u8>,
+}
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+const LINES: u32 = 1;
+const TRIANGLES: u32 = 4;
+
+const WIREFRAME_MATERIAL: usize = 0;
+const CAVE_MATERIAL: usize = 1;
+
+impl Builder {
+    fn buffer_view(&mut self, bytes: impl IntoIterator<Item = [u8; 4]>, target: u32) -> usize {
+        let offset = self.buffer.len();
+        self.buffer.extend(bytes.into_iter().flatten());
+        self.buffer_views.push(json!({
+            "buffer": 0,
+            "byteOffset": offset,
+            "byteLength": self.buffer.len() - offset,
+            "target": target,
+        }));
+        self.buffer_views.len() - 1
+    }
+
+    fn vectors(&mut self, vectors: &[Vector3<f32>], bounds: bool) -> usize {
+        let view = self.buffer_view(
+            vectors
+                .iter()
+                .flat_map(|v| [v.x, v.y, v.z].map(f32::to_le_bytes)),
+            ARRAY_BUFFER,
+        );
+        let mut accessor = json!({
+            "bufferView": view,
+            "componentType": FLOAT,
+            "count": vectors.len(),
+            "type": "VEC3",
+        });
+        // required for POSITION accessors
+        if bounds {
+            let fold = |f: fn(f32, f32) -> f32, init: f32| {
+                let v = vectors.iter().fold(vec3(init, init, init), |a, v| {
+                    vec3(f(a.x, v.x), f(a.y, v.y), f(a.z, v.z))
+                });
+                [v.x, v.y, v.z]
+            };
+            accessor["min"] = json!(fold(f32::min, f32::INFINITY));
+            accessor["max"] = json!(fold(f32::max, f32::NEG_INFINITY));
+        }
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn indices(&mut self, indices: &[u32]) -> usize {
+        let view = self.buffer_view(
+            indices.iter().map(|i| i.to_le_bytes()),
+            ELEMENT_ARRAY_BUFFER,
+        );
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": UNSIGNED_INT,
+            "count": indices.len(),
+            "type": "SCALAR",
+        }));
+        self.accessors.len() - 1
+    }
+
+    fn node(&mut self, node: Value) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    fn mesh(&mut self, name: &str, primitive: Value) -> usize {
+        self.meshes
+            .push(json!({ "name": name, "primitives": [primitive] }));
+        self.meshes.len() - 1
+    }
+
+    /// Line mesh of `segments`, given in UE space, relative to the node at `world`.
+    fn wireframe(&mut self, name: &str, segments: &[Segment], world: Transform) -> usize {
+        let positions = segments
+            .iter()
+            .flatten()
+            .map(|&p| world.inverse_apply(to_gltf(p)))
+            .collect::<Vec<_>>();
+        let accessor = self.vectors(&positions, true);
+        self.mesh(
+            name,
+            json!({
+                "attributes": { "POSITION": accessor },
+                "mode": LINES,
+                "material": WIREFRAME_MATERIAL,
+            }),
+        )
+    }
+
+    fn cave(&mut self, mesh: &SurfaceMesh) -> usize {
+        let positions = mesh
+            .positions
+            .iter()
+            .map(|&p| to_gltf(p))
+            .collect::<Vec<_>>();
+        let normals = mesh
+            .normals
+            .iter()
+            .map(|&n| to_gltf(n) / SCALE)
+            .collect::<Vec<_>>();
+        // the axis swap mirrors the mesh so the winding has to be flipped
+        let indices = mesh
+            .indices
+            .chunks_exact(3)
+            .flat_map(|t| [t[0], t[2], t[1]])
+            .collect::<Vec<_>>();
+        let attributes = json!({
+            "POSITION": self.vectors(&positions, true),
+            "NORMAL": self.vectors(&normals, false),
+        });
+        let indices = self.indices(&indices);
+        let mesh = self.mesh(
+            "cave",
+            json!({
+                "attributes": attributes,
+                "indices": indices,
+                "mode": TRIANGLES,
+                "material": CAVE_MATERIAL,
+            }),
+        );
+        self.node(json!({ "name": "cave", "mesh": mesh, "extras": { CAVE_KEY: true } }))
+    }
+
+    /// Add the node for `feature` and its children, returning its index.
+    fn feature(
+        &mut self,
+        feature: &RoomFeature,
+        path: &mut FeaturePath,
+        parent: Transform,
+    ) -> usize {
+        let name = format!("{} {}", feature.name().trim(), format_path(path));
+        let world = feature_transform(feature).unwrap_or(parent);
+        let local = world.relative_to(parent);
+
+        let mut children = vec![];
+        let mut points = |builder: &mut Self, locations: &mut dyn Iterator<Item = Vector3<f32>>| {
+            for (i, location) in locations.enumerate() {
+                let point = Transform {
+                    translation: to_gltf(location),
+                    rotation: Transform::IDENTITY.rotation,
+                }
+                .relative_to(world);
+                let t = point.translation;
+                children.push(builder.node(json!({
+                    "name": format!("{name} point {i}"),
+                    "translation": [t.x, t.y, t.z],
+                    "extras": { PATH_KEY: path, POINT_KEY: i },
+                })));
+            }
+        };
+        let segments = match feature {
+            RoomFeature::FloodFillLine(f) => {
+                points(self, &mut f.points.iter().map(|p| p.location.into()));
+                f.wireframe()
+            }
+            RoomFeature::FloodFillPillar(f) => {
+                points(self, &mut f.points.iter().map(|p| p.location.into()));
+                f.wireframe()
+            }
+            RoomFeature::FloodFillBox(f) => f.wireframe(),
+            _ => vec![],
+        };
+        let mesh = (!segments.is_empty()).then(|| self.wireframe(&name, &segments, world));
+
+        for (i, child) in feature.children().iter().enumerate() {
+            path.push(i);
+            children.push(self.feature(child, path, world));
+            path.pop();
+        }
+
+        // parameters without the child features, which have nodes of their own
+        let mut params = feature.clone();
+        if let Some(base) = params.base_mut() {
+            base.room_features.clear();
+        }
+
+        let (t, r) = (local.translation, local.rotation);
+        let mut node = json!({
+            "name": name,
+            "translation": [t.x, t.y, t.z],
+            "rotation": [r.v.x, r.v.y, r.v.z, r.s],
+            "extras": { PATH_KEY: path, "feature": params },
+        });
+        if let Some(mesh) = mesh {
+            node["mesh"] = json!(mesh);
+        }
+        if !children.is_empty() {
+            node["children"] = json!(children);
+        }
+        self.node(node)
+    }
+}
+
+/// Write `room` and optionally its cave surface as a binary glTF.
+pub fn write_glb<W: Write>(
+    room: &RoomGenerator,
+    cave: Option<&SurfaceMesh>,
+    mut w: W,
+) -> Result<()> {
+    let mut builder = Builder::default();
+    let mut children = room
+        .room_features
+        .iter()
+        .enumerate()
+        .map(|(i, f)| builder.feature(f, &mut vec![i], Transform::IDENTITY))
+        .collect::<Vec<_>>();
+    if let Some(cave) = cave {
+        children.push(builder.cave(cave));
+    }
+    let root = builder.node(json!({
+        "name": "RoomGenerator",
+        "children": children,
+        "extras": { ROOM_KEY: room.base },
+    }));
+
+    let mut buffer = builder.buffer;
+    let mut gltf = json!({
+        "asset": { "version": "2.0", "generator": "rma-editor" },
+        "scene": 0,
+        "scenes": [{ "nodes": [root] }],
+        "nodes": builder.nodes,
+        "meshes": builder.meshes,
+        "materials": [
+            {
+                "name": "wireframe",
+                "pbrMetallicRoughness": { "baseColorFactor": [1.0, 0.0, 0.0, 1.0] },
+            },
+            {
+                "name": "cave",
+                "pbrMetallicRoughness": {
+                    "baseColorFactor": [0.63, 0.51, 0.43, 1.0],
+                    "metallicFactor": 0.0,
+                },
+                "doubleSided": true,
+            },
+        ],
+    });
+    if !buffer.is_empty() {
+        gltf["accessors"] = json!(builder.accessors);
+        gltf["bufferViews"] = json!(builder.buffer_views);
+        gltf["buffers"] = json!([{ "byteLength": buffer.len() }]);
+    }
+
+    let mut json = serde_json::to_vec(&gltf)?;
+    json.resize(json.len().next_multiple_of(4), b' ');
+    buffer.resize(buffer.len().next_multiple_of(4), 0);
+
+    let chunks = [(json, JSON_CHUNK), (buffer, BIN_CHUNK)];
+    let chunks = chunks.iter().filter(|(data, _)| !data.is_empty());
+    let length = 12
+        + chunks
+            .clone()
+            .map(|(data, _)| 8 + data.len())
+            .sum::<usize>();
+    w.write_all(b"glTF")?;
+    w.write_all(&2u32.to_le_bytes())?;
+    w.write_all(&u32::try_from(length)?.to_le_bytes())?;
+    for (data, kind) in chunks {
+        w.write_all(&u32::try_from(data.len())?.to_le_bytes())?;
+        w.write_all(&kind.to_le_bytes())?;
+        w.write_all(data)?;
+    }
+    Ok(())
+}
+
+/// Outcome of [`RoomGenerator::apply_gltf`].
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    /// Features, with the point index for line and pillar points, whose placement changed.
+    pub updated: Vec<(FeaturePath, Option<usize>)>,
+    /// Names of nodes with a stored path that doesn't match a feature or point of the room.
+    pub unmatched: Vec<String>,
+    /// Names of nodes without a stored path, e.g. ones added in the editing tool.
+    pub added: Vec<String>,
+}
+
+const JSON_CHUNK: u32 = 0x4E4F534A;
+const BIN_CHUNK: u32 = 0x004E4942;
+
+/// JSON of a `.glb`, or of a plain `.gltf`.
+fn parse(data: &[u8]) -> Result<Value> {
+    let Some(header) = data.strip_prefix(b"glTF") else {
+        return Ok(serde_json::from_slice(data)?);
+    };
+    let word = |i: usize| -> Result<u32> {
+        let bytes = header.get(i..i + 4).context("truncated glTF")?;
+        Ok(u32::from_le_bytes(bytes.try_into()?))
+    };
+    let (length, kind) = (word(8)? as usize, word(12)?);
+    if kind != JSON_CHUNK {
+        bail!("first glTF chunk is not JSON");
+    }
+    let json = header.get(16..16 + length).context("truncated glTF")?;
+    Ok(serde_json::from_slice(json)?)
+}
+
+fn floats<const N: usize>(value: Option<&Value>) -> Option<[f32; N]> {
+    let array = value?.as_array()?;
+    if array.len() != N {
+        return None;
+    }
+    let mut floats = [0.0; N];
+    for (f, v) in floats.iter_mut().zip(array) {
+        *f = v.as_f64()? as f32;
+    }
+    Some(floats)
+}
+
+fn local_matrix(node: &Value) -> Mat4 {
+    if let Some(m) = floats::<16>(node.get("matrix")) {
+        let column = |i: usize| [m[4 * i], m[4 * i + 1], m[4 * i + 2], m[4 * i + 3]];
+        return Mat4::from([column(0), column(1), column(2), column(3)]);
+    }
+    let [tx, ty, tz] = floats(node.get("translation")).unwrap_or([0.0; 3]);
+    let [x, y, z, w] = floats(node.get("rotation")).unwrap_or([0.0, 0.0, 0.0, 1.0]);
+    let [sx, sy, sz] = floats(node.get("scale")).unwrap_or([1.0; 3]);
+    Mat4::from_translation(vec3(tx, ty, tz))
+        * Mat4::from(Quat::new(w, x, y, z))
+        * Mat4::from_nonuniform_scale(sx, sy, sz)
+}
+
+/// Translation and rotation of an affine matrix, ignoring any scale.
+fn decompose(m: Mat4) -> Transform {
+    let axis = |c: Vector3<f32>| c.normalize();
+    Transform {
+        translation: m.w.truncate(),
+        rotation: Quat::from(Mat3::from_cols(
+            axis(m.x.truncate()),
+            axis(m.y.truncate()),
+            axis(m.z.truncate()),
+        )),
+    }
+}
+
+/// Store `value` if it moved by more than a millimeter, returning whether it did.
+fn set_location(target: &mut FVector, value: Vector3<f32>) -> bool {
+    let current: Vector3<f32> = (*target).into();
+    if (current - value).magnitude() < 0.1 {
+        return false;
+    }
+    *target = FVector {
+        x: value.x,
+        y: value.y,
+        z: value.z,
+    };
+    true
+}
+
+/// Whether `a` and `b` differ by more than a fraction of a degree.
+fn rotation_changed(a: Quat, b: Quat) -> bool {
+    a.dot(b).abs() < 1.0 - 1e-6
+}
+
+fn set_rotator(target: &mut FRotator, value: Quat) -> bool {
+    let changed = rotation_changed((*target).into(), value);
+    if changed {
+        *target = value.into();
+    }
+    changed
+}
+
+fn set_quat(target: &mut FQuat, value: Quat) -> bool {
+    let changed = rotation_changed((*target).into(), value);
+    if changed {
+        *target = value.into();
+    }
+    changed
+}
+
+impl RoomGenerator {
+    /// Apply the locations and rotations of nodes in a glTF previously written by
+    /// [`write_glb`], matched back to features by the path stored in their `extras`.
+    ///
+    /// Paths refer to the feature tree that was exported, so this only makes sense on the same
+    /// room and not on a different variant of it.
+    pub fn apply_gltf(&mut self, data: &[u8]) -> Result<ImportReport> {
+        let gltf = parse(data)?;
+        let empty = vec![];
+        let nodes = gltf["nodes"].as_array().unwrap_or(&empty);
+        let indices = |v: &Value| {
+            v.as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|i| i.as_u64().map(|i| i as usize))
+                .collect::<Vec<_>>()
+        };
+        let roots = gltf["scenes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|scene| indices(&scene["nodes"]))
+            .collect::<Vec<_>>();
+
+        let mut report = ImportReport::default();
+        let mut visited = vec![false; nodes.len()];
+        let mut stack = roots
+            .into_iter()
+            .map(|i| (i, Mat4::from_scale(1.0)))
+            .collect::<Vec<_>>();
+        while let Some((i, parent)) = stack.pop() {
+            let Some(node) = nodes.get(i) else {
+                bail!("node {i} does not exist");
+            };
+            if std::mem::replace(&mut visited[i], true) {
+                continue;
+            }
+            let world = parent * local_matrix(node);
+            stack.extend(indices(&node["children"]).into_iter().map(|c| (c, world)));
+
+            let name = node["name"].as_str().unwrap_or("unnamed").to_string();
+            let extras = &node["extras"];
+            if !extras[ROOM_KEY].is_null() || !extras[CAVE_KEY].is_null() {
+                continue;
+            }
+            let Ok(path) = serde_json::from_value::<FeaturePath>(extras[PATH_KEY].clone()) else {
+                report.added.push(name);
+                continue;
+            };
+            let point = extras[POINT_KEY].as_u64().map(|p| p as usize);
+
+            let world = decompose(world);
+            let location = from_gltf(world.translation);
+            let rotation = convert_rotation(world.rotation);
+            let changed = match (self.feature_mut(&path), point) {
+                (Some(RoomFeature::FloodFillLine(f)), Some(point)) => f
+                    .points
+                    .get_mut(point)
+                    .map(|p| set_location(&mut p.location, location)),
+                (Some(RoomFeature::FloodFillPillar(f)), Some(point)) => f
+                    .points
+                    .get_mut(point)
+                    .map(|p| set_location(&mut p.location, location)),
+                (None, _) | (Some(_), Some(_)) => None,
+                (Some(RoomFeature::FloodFillBox(f)), None) => Some(
+                    set_location(&mut f.position, location)
+                        | set_rotator(&mut f.rotation, rotation),
+                ),
+                (Some(RoomFeature::EntranceFeature(f)), None) => Some(
+                    set_location(&mut f.location, location)
+                        | set_rotator(&mut f.direction, rotation),
+                ),
+                (Some(RoomFeature::SpawnTriggerFeature(f)), None) => Some(
+                    set_location(&mut f.transform.translation, location)
+                        | set_quat(&mut f.transform.rotation, rotation),
+                ),
+                (Some(RoomFeature::SpawnActorFeature(f)), None) => {
+                    Some(set_location(&mut f.location, location))
+                }
+                (Some(RoomFeature::ResourceFeature(f)), None) => {
+                    Some(set_location(&mut f.location, location))
+                }
+                (Some(RoomFeature::DropPodCalldownLocationFeature(f)), None) => {
+                    Some(set_location(&mut f.location, location))
+                }
+                // placed by their points or children
+                (Some(_), None) => Some(false),
+            };
+            match changed {
+                Some(true) => report.updated.push((path, point)),
+                Some(false) => {}
+                None => report.unmatched.push(name),
+            }
+        }
+        report.updated.sort();
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rma::{
+        EntranceFeature, FRoomLinePoint, FTransform, FloodFillLine, RandomSelector,
+        SpawnTriggerFeature,
+    };
+
+    fn room() -> RoomGenerator {
+        let point = |x: f32| FRoomLinePoint {
+            location: FVector {
+                x,
+                y: 100.0,
+                z: 0.0,
+            },
+            h_range: 400.0,
+            v_range: 300.0,
+            ..Default::default()
+        };
+        let entrance = EntranceFeature {
+            location: FVector {
+                x: 0.0,
+                y: 500.0,
+                z: 200.0,
+            },
+            direction: FRotator {
+                pitch: 10.0,
+                yaw: 30.0,
+                roll: 0.0,
+            },
+            ..Default::default()
+        };
+        let mut line = FloodFillLine {
+            points: vec![point(0.0), point(2000.0)],
+            ..Default::default()
+        };
+        line.base.room_features = vec![RoomFeature::EntranceFeature(entrance)];
+        let mut selector = RandomSelector {
+            min: 1,
+            max: 1,
+            ..Default::default()
+        };
+        selector.base.room_features = vec![RoomFeature::FloodFillLine(line)];
+        RoomGenerator {
+            room_features: vec![RoomFeature::RandomSelector(selector)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let mut room = room();
+        let mut glb = vec![];
+        write_glb(&room, None, &mut glb)?;
+
+        let report = room.apply_gltf(&glb)?;
+        assert!(report.updated.is_empty(), "{report:?}");
+        assert!(report.unmatched.is_empty() && report.added.is_empty());
+
+        // move the last line point and turn the entrance around, as an artist would
+        let mut gltf = parse(&glb)?;
+        let nodes = gltf["nodes"].as_array_mut().unwrap();
+        for node in nodes.iter_mut() {
+            if node["extras"][POINT_KEY] == 1 {
+                node["translation"][0] = json!(25.0);
+            }
+            if node["extras"][PATH_KEY] == json!([0, 0, 0]) {
+                let q = convert_rotation(
+                    FRotator {
+                        pitch: 10.0,
+                        yaw: -150.0,
+                        roll: 0.0,
+                    }
+                    .into(),
+                );
+                node["rotation"] = json!([q.v.x, q.v.y, q.v.z, q.s]);
+            }
+        }
+        nodes.push(json!({ "name": "added" }));
+        let added = nodes.len() - 1;
+        gltf["scenes"][0]["nodes"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!(added));
+
+        let report = room.apply_gltf(&serde_json::to_vec(&gltf)?)?;
+        assert_eq!(
+            report.updated,
+            [(vec![0, 0], Some(1)), (vec![0, 0, 0], None)]
+        );
+        assert_eq!(report.added, ["added"]);
+
+        let RoomFeature::FloodFillLine(line) = room.feature(&[0, 0]).unwrap() else {
+            panic!("expected a line");
+        };
+        assert!((line.points[1].location.x - 2500.0).abs() < 0.01);
+        assert!((line.points[1].location.y - 100.0).abs() < 0.01);
+        let RoomFeature::EntranceFeature(entrance) = room.feature(&[0, 0, 0]).unwrap() else {
+            panic!("expected an entrance");
+        };
+        assert!((entrance.direction.yaw + 150.0).abs() < 0.01);
+        assert!((entrance.direction.pitch - 10.0).abs() < 0.01);
+        Ok(())
+    }
+
+    #[test]
+    fn test_spawn_trigger_rotation() -> Result<()> {
+        let rotation = |yaw| -> Quat {
+            FRotator {
+                pitch: 20.0,
+                yaw,
+                roll: 10.0,
+            }
+            .into()
+        };
+        let trigger = SpawnTriggerFeature {
+            transform: FTransform {
+                translation: FVector {
+                    x: 100.0,
+                    y: 200.0,
+                    z: 300.0,
+                },
+                rotation: rotation(40.0).into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut room = RoomGenerator {
+            room_features: vec![RoomFeature::SpawnTriggerFeature(trigger)],
+            ..Default::default()
+        };
+        let mut glb = vec![];
+        write_glb(&room, None, &mut glb)?;
+
+        let mut gltf = parse(&glb)?;
+        let node = gltf["nodes"]
+            .as_array_mut()
+            .unwrap()
+            .iter_mut()
+            .find(|node| node["extras"][PATH_KEY] == json!([0]))
+            .unwrap();
+        let [x, y, z, w] = floats(node.get("rotation")).unwrap();
+        let exported = Quat::new(w, x, y, z);
+        assert!((exported.magnitude() - 1.0).abs() < 1e-5);
+        assert!(!rotation_changed(
+            exported,
+            convert_rotation(rotation(40.0))
+        ));
+        assert!(room.apply_gltf(&glb)?.updated.is_empty());
+
+        let q = convert_rotation(rotation(-70.0));
+        node["rotation"] = json!([q.v.x, q.v.y, q.v.z, q.s]);
+        let report = room.apply_gltf(&serde_json::to_vec(&gltf)?)?;
+        assert_eq!(report.updated, [(vec![0], None)]);
+        let RoomFeature::SpawnTriggerFeature(trigger) = &room.room_features[0] else {
+            panic!("expected a spawn trigger");
+        };
+        let imported = Quat::from(trigger.transform.rotation);
+        assert!((imported.magnitude() - 1.0).abs() < 1e-5);
+        assert!(!rotation_changed(imported, rotation(-70.0)));
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{
+    random::FRandomStream,
+    rma::{
+        FRandRange, FloodFillPillar, RandomSelector, RoomFeature, RoomGenerator, SpawnActorFeature,
+    },
+};
+
+impl RandomSelector {
+    /// Pick which children get generated, returned as indices into `base.room_features` in
+    /// their original order.
+    fn select(&self, stream: &mut FRandomStream) -> Vec<usize> {
+        let children = self.base.room_features.len();
+        let count = stream
+            .rand_range(self.min, self.max)
+            .clamp(0, children as i32) as usize;
+
+        let mut candidates = (0..children).collect::<Vec<_>>();
+        let mut selected = Vec::with_capacity(count);
+        for _ in 0..count {
+            let i = stream.rand_helper(candidates.len() as i32) as usize;
+            selected.push(candidates.remove(i));
+        }
+        selected.sort();
+        selected
+    }
+}
+
+struct Resolver {
+    stream: FRandomStream,
+    /// Whether sampled values replace the ranges they were drawn from. The stream is consumed
+    /// the same way either way so both modes select the same features.
+    sample_ranges: bool,
+}
+
+impl Resolver {
+    fn range(&mut self, range: &mut FRandRange) {
+        let value = range.sample(&mut self.stream);
+        if self.sample_ranges {
+            range.min = value;
+            range.max = value;
+        }
+    }
+
+    fn pillar(&mut self, pillar: &mut FloodFillPillar) {
+        self.range(&mut pillar.range_scale);
+        self.range(&mut pillar.noise_range_scale);
+        self.range(&mut pillar.endcap_scale);
+        for point in &mut pillar.points {
+            self.range(&mut point.range);
+            self.range(&mut point.noise_range);
+            self.range(&mut point.skew_factor);
+            self.range(&mut point.fill_amount);
+        }
+    }
+
+    fn spawn_actor(&mut self, spawn: &mut SpawnActorFeature) {
+        let scale = spawn.sample_scale(&mut self.stream);
+        // there is no field to store the sampled rotation in so `rotation_delta` is kept as is
+        spawn.sample_rotation(&mut self.stream);
+        if self.sample_ranges {
+            spawn.scale_min = scale;
+            spawn.scale_max = scale;
+        }
+    }
+
+    fn resolve(&mut self, features: &[RoomFeature], out: &mut Vec<RoomFeature>) {
+        for feature in features {
+            match feature {
+                RoomFeature::RandomSelector(selector) => {
+                    for i in selector.select(&mut self.stream) {
+                        self.resolve(std::slice::from_ref(&selector.base.room_features[i]), out);
+                    }
+                }
+                _ => {
+                    let mut feature = feature.clone();
+                    match &mut feature {
+                        RoomFeature::FloodFillPillar(pillar) => self.pillar(pillar),
+                        RoomFeature::SpawnActorFeature(spawn) => self.spawn_actor(spawn),
+                        _ => {}
+                    }
+                    // sub rooms are not imported so they have no children to resolve
+                    if let Some(base) = feature.base_mut() {
+                        let children = std::mem::take(&mut base.room_features);
+                        self.resolve(&children, &mut base.room_features);
+                    }
+                    out.push(feature);
+                }
+            }
+        }
+    }
+}
+
+impl RoomGenerator {
+    fn resolve(&self, seed: i32, sample_ranges: bool) -> RoomGenerator {
+        let mut resolver = Resolver {
+            stream: FRandomStream::new(seed),
+            sample_ranges,
+        };
+        let mut room_features = vec![];
+        resolver.resolve(&self.room_features, &mut room_features);
+        RoomGenerator {
+            base: self.base.clone(),
+            room_features,
+        }
+    }
+
+    /// Resolve every `RandomSelector` using `seed`, returning a concrete room where the selected
+    /// children take the place of their selector. See [`crate::random`] for how the stream is
+    /// consumed.
+    pub fn instantiate(&self, seed: i32) -> RoomGenerator {
+        self.resolve(seed, false)
+    }
+
+    /// Like [`RoomGenerator::instantiate`] but additionally collapses every `FRandRange` and
+    /// spawn scale range to the value sampled for this seed.
+    pub fn instantiate_sampled(&self, seed: i32) -> RoomGenerator {
+        self.resolve(seed, true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rma::{EntranceFeature, FVector, RoomFeatureBase};
+
+    use super::*;
+
+    #[test]
+    fn test_instantiate() {
+        let entrance = |x| {
+            RoomFeature::EntranceFeature(EntranceFeature {
+                location: FVector { x, y: 0.0, z: 0.0 },
+                ..Default::default()
+            })
+        };
+        let room = RoomGenerator {
+            room_features: vec![
+                entrance(-1.0),
+                RoomFeature::RandomSelector(RandomSelector {
+                    base: RoomFeatureBase {
+                        room_features: vec![entrance(0.0), entrance(1.0), entrance(2.0)],
+                    },
+                    min: 1,
+                    max: 2,
+                }),
+            ],
+            ..Default::default()
+        };
+        let mut counts = [0; 3];
+        for seed in 0..100 {
+            let instance = room.instantiate(seed);
+            assert_eq!(
+                format!("{:?}", instance.room_features),
+                format!("{:?}", room.instantiate(seed).room_features)
+            );
+            let xs = instance
+                .room_features
+                .iter()
+                .map(|f| f.location().unwrap().x)
+                .collect::<Vec<_>>();
+            assert_eq!(xs[0], -1.0);
+            // selected children replace the selector in their original order
+            assert!(xs.windows(2).all(|w| w[0] < w[1]), "{xs:?}");
+            counts[xs.len() - 1] += 1;
+        }
+        // the selector always picks one or two of its children
+        assert_eq!(counts[0], 0);
+        assert!(counts[1] > 0 && counts[2] > 0, "{counts:?}");
+    }
+}
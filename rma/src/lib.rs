@@ -1,6 +1,27 @@
 #![allow(special_module_name)]
+pub mod analysis;
+pub mod bounds;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
+pub mod export;
+pub mod gltf;
+pub mod instance;
+pub mod lint;
+pub mod lint_config;
+pub mod mesh;
+pub mod noise;
+pub mod random;
+pub mod raster;
 pub mod rma;
 pub mod room_features;
+pub mod slice;
+pub mod snap;
+pub mod stats;
+pub mod svg;
+pub mod tube;
+pub mod voxel;
+pub mod walk;
+pub mod wireframe;
 
 #[cfg(target_arch = "wasm32")]
 mod main;
@@ -22,7 +43,11 @@ use unreal_asset::{
 pub struct RMAContext<'c> {
     pub context: &'c Context,
     pub wireframe_material: PhysicalMaterial,
+    /// Faint material for the extent of noise around features.
+    pub noise_material: PhysicalMaterial,
     pub wireframe_mesh: CpuMesh,
+    /// Draw lines and pillars as surfaces rather than wireframes.
+    pub surfaces: bool,
 }
 
 pub enum AppMode {
@@ -0,0 +1,521 @@
+//! Checks for common mistakes in rooms.
+//!
+//! Each [`Rule`] looks at one kind of problem and reports it as a [`Diagnostic`] pointing at the
+//! offending feature. Rules checking space use a [`DensityGrid`] carved from every feature, so
+//! they describe the union of all variants rather than a single one. Rules can be configured per
+//! project and suppressed per room, see [`crate::lint_config`].
+
+use std::{collections::HashMap, fmt};
+
+use serde::{Deserialize, Serialize};
+use three_d_asset::Vector3;
+
+use crate::{
+    lint_config::Config,
+    rma::{format_path, iter_features, FRandRange, FeaturePath, RoomFeature, RoomGenerator},
+    snap::{self, SnapOutcome},
+    voxel::{CarveSettings, DensityGrid},
+    walk::{self, Reach},
+};
+
+/// Default open space needed above a drop pod location for the pod to land, roughly its height.
+pub const DROP_POD_HEADROOM: f32 = 600.0;
+/// How far below the floor a drop pod location may be and still count as standing on it.
+const FLOOR_TOLERANCE: f32 = 100.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// Some or all variants of the room have no entrance.
+    NoEntrances,
+    /// An entrance is not in carved out space.
+    EntranceInRock,
+    /// A random selector picks an impossible number of children.
+    SelectorCount,
+    /// A line or pillar has fewer than two points.
+    TooFewPoints,
+    /// A random range has its minimum above its maximum.
+    InvertedRange,
+    /// A drop pod location lacks open space above it.
+    DropPodClearance,
+    /// The room has the same tag more than once.
+    DuplicateTag,
+    /// A feature reaches outside the room's declared bounds.
+    OutOfBounds,
+    /// An adjusted spawn starts in rock or never reaches a surface.
+    SpawnSnap,
+    /// The room declares bounds above the configured maximum.
+    BoundsTooLarge,
+    /// The room lacks a tag its name requires.
+    RequiredTag,
+    /// The room's entrance count is outside what its name requires.
+    EntranceCount,
+    /// An entrance or drop pod location cannot be walked to from the others.
+    Unreachable,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 13] = [
+        Rule::NoEntrances,
+        Rule::EntranceInRock,
+        Rule::SelectorCount,
+        Rule::TooFewPoints,
+        Rule::InvertedRange,
+        Rule::DropPodClearance,
+        Rule::DuplicateTag,
+        Rule::OutOfBounds,
+        Rule::SpawnSnap,
+        Rule::BoundsTooLarge,
+        Rule::RequiredTag,
+        Rule::EntranceCount,
+        Rule::Unreachable,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::NoEntrances => "no-entrances",
+            Rule::EntranceInRock => "entrance-in-rock",
+            Rule::SelectorCount => "selector-count",
+            Rule::TooFewPoints => "too-few-points",
+            Rule::InvertedRange => "inverted-range",
+            Rule::DropPodClearance => "drop-pod-clearance",
+            Rule::DuplicateTag => "duplicate-tag",
+            Rule::OutOfBounds => "out-of-bounds",
+            Rule::SpawnSnap => "spawn-snap",
+            Rule::BoundsTooLarge => "bounds-too-large",
+            Rule::RequiredTag => "required-tag",
+            Rule::EntranceCount => "entrance-count",
+            Rule::Unreachable => "unreachable",
+        }
+    }
+
+    /// Severity the rule is reported at unless configured otherwise.
+    pub fn severity(self) -> Severity {
+        match self {
+            Rule::NoEntrances
+            | Rule::SelectorCount
+            | Rule::TooFewPoints
+            | Rule::RequiredTag
+            | Rule::EntranceCount => Severity::Error,
+            Rule::DuplicateTag => Severity::Info,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub severity: Severity,
+    /// Feature the diagnostic is about, `None` for the room as a whole.
+    pub path: Option<FeaturePath>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.severity)?;
+        if let Some(path) = &self.path {
+            write!(f, " {}", format_path(path))?;
+        }
+        write!(f, ": {} [{}]", self.message, self.rule)
+    }
+}
+
+struct Lint<'a> {
+    config: &'a Config,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Lint<'_> {
+    fn report(&mut self, rule: Rule, path: Option<&[usize]>, message: String) {
+        let Some(severity) = self.config.severity(rule) else {
+            return;
+        };
+        self.diagnostics.push(Diagnostic {
+            rule,
+            severity,
+            path: path.map(<[usize]>::to_vec),
+            message,
+        });
+    }
+
+    fn range(&mut self, path: &[usize], name: &str, range: &FRandRange) {
+        if range.min > range.max {
+            self.report(
+                Rule::InvertedRange,
+                Some(path),
+                format!(
+                    "`{name}` has a minimum of {} above its maximum of {}",
+                    range.min, range.max
+                ),
+            );
+        }
+    }
+
+    fn feature(&mut self, feature: &RoomFeature, path: &[usize]) {
+        match feature {
+            RoomFeature::RandomSelector(selector) => {
+                let children = selector.base.room_features.len();
+                if selector.min > selector.max {
+                    self.report(
+                        Rule::SelectorCount,
+                        Some(path),
+                        format!(
+                            "picks at least {} but at most {} children",
+                            selector.min, selector.max
+                        ),
+                    );
+                } else if selector.max > children as i32 {
+                    self.report(
+                        Rule::SelectorCount,
+                        Some(path),
+                        format!(
+                            "picks up to {} children but only has {children}",
+                            selector.max
+                        ),
+                    );
+                }
+            }
+            RoomFeature::FloodFillLine(line) if line.points.len() < 2 => {
+                self.report(
+                    Rule::TooFewPoints,
+                    Some(path),
+                    format!("line has {} points", line.points.len()),
+                );
+            }
+            RoomFeature::FloodFillPillar(pillar) => {
+                if pillar.points.len() < 2 {
+                    self.report(
+                        Rule::TooFewPoints,
+                        Some(path),
+                        format!("pillar has {} points", pillar.points.len()),
+                    );
+                }
+                self.range(path, "range_scale", &pillar.range_scale);
+                self.range(path, "noise_range_scale", &pillar.noise_range_scale);
+                self.range(path, "endcap_scale", &pillar.endcap_scale);
+                for (i, point) in pillar.points.iter().enumerate() {
+                    self.range(path, &format!("points[{i}].range"), &point.range);
+                    self.range(
+                        path,
+                        &format!("points[{i}].noise_range"),
+                        &point.noise_range,
+                    );
+                    self.range(
+                        path,
+                        &format!("points[{i}].skew_factor"),
+                        &point.skew_factor,
+                    );
+                    self.range(
+                        path,
+                        &format!("points[{i}].fill_amount"),
+                        &point.fill_amount,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn space(&mut self, room: &RoomGenerator, grid: &DensityGrid) {
+        iter_features(
+            &room.room_features,
+            &mut vec![],
+            &mut |feature, path| match feature {
+                RoomFeature::EntranceFeature(f) if !grid.is_open(f.location.into()) => {
+                    self.report(
+                        Rule::EntranceInRock,
+                        Some(path),
+                        "entrance is not in carved out space".to_string(),
+                    );
+                }
+                RoomFeature::DropPodCalldownLocationFeature(f) => {
+                    let location = Vector3::from(f.location);
+                    let headroom = grid
+                        .clearance(location.x, location.y)
+                        .into_iter()
+                        .filter(|span| span.start - FLOOR_TOLERANCE <= location.z)
+                        .map(|span| span.end - location.z.max(span.start))
+                        .fold(0.0f32, f32::max);
+                    let needed = self.config.drop_pod_headroom;
+                    if headroom < needed {
+                        self.report(
+                            Rule::DropPodClearance,
+                            Some(path),
+                            format!(
+                                "{headroom:.0} units of open space above the drop pod, \
+                                 {needed:.0} needed"
+                            ),
+                        );
+                    }
+                }
+                _ => {}
+            },
+        );
+
+        let distance = self.config.max_snap_distance;
+        for snap in snap::simulate(room, grid, distance) {
+            let message = match snap.outcome {
+                SnapOutcome::Hit { .. } => continue,
+                SnapOutcome::Missed => {
+                    format!("spawn never reaches rock within {distance:.0} units")
+                }
+                SnapOutcome::InRock => "spawn starts in rock".to_string(),
+            };
+            self.report(Rule::SpawnSnap, Some(&snap.path), message);
+        }
+
+        let connectivity = walk::analyze(room, grid, &self.config.walk);
+        let root = connectivity.root.map(|i| &connectivity.routes[i]);
+        for route in &connectivity.routes {
+            let kind = route.kind.name();
+            let message = match (&route.reach, root) {
+                (Reach::Connected { .. }, _) => continue,
+                // in rock, which the entrance and drop pod rules already report
+                (Reach::NoFloor, _) if !grid.is_open(route.location) => continue,
+                (Reach::NoFloor, _) => format!("{kind} has no floor with room for a dwarf"),
+                (Reach::Disconnected, Some(root)) => format!(
+                    "{kind} cannot be walked to from the {} at {}",
+                    root.kind.name(),
+                    format_path(&root.path)
+                ),
+                (Reach::Disconnected, None) => format!("{kind} cannot be walked to"),
+            };
+            self.report(Rule::Unreachable, Some(&route.path), message);
+        }
+    }
+}
+
+/// Run every rule against `room` with the default configuration.
+pub fn check(room: &RoomGenerator) -> Vec<Diagnostic> {
+    check_with(room, None, &Config::default())
+}
+
+/// Run the rules enabled in `config` against `room`, room wide diagnostics first and then by
+/// feature. `name` is the room's asset name, without it name specific requirements are skipped.
+pub fn check_with(room: &RoomGenerator, name: Option<&str>, config: &Config) -> Vec<Diagnostic> {
+    let mut lint = Lint {
+        config,
+        diagnostics: vec![],
+    };
+
+    let entrances = room.analyze_outcomes().entrances;
+    if entrances.max == 0 {
+        lint.report(Rule::NoEntrances, None, "room has no entrances".to_string());
+    } else if entrances.min == 0 {
+        lint.report(
+            Rule::NoEntrances,
+            None,
+            "some variants of the room have no entrances".to_string(),
+        );
+    }
+
+    let mut tags = HashMap::<&str, usize>::new();
+    for tag in &room.base.room_tags.tags {
+        *tags.entry(tag.as_str()).or_default() += 1;
+    }
+    let mut duplicates = tags.into_iter().filter(|(_, n)| *n > 1).collect::<Vec<_>>();
+    duplicates.sort();
+    for (tag, count) in duplicates {
+        lint.report(
+            Rule::DuplicateTag,
+            None,
+            format!("tag `{tag}` appears {count} times"),
+        );
+    }
+
+    if let Some(max) = config.max_bounds.filter(|&max| room.base.bounds > max) {
+        lint.report(
+            Rule::BoundsTooLarge,
+            None,
+            format!(
+                "bounds of {:.0} are above the maximum of {max:.0}",
+                room.base.bounds
+            ),
+        );
+    }
+
+    for requirements in name.into_iter().flat_map(|name| config.requirements(name)) {
+        let pattern = &requirements.name;
+        for tag in &requirements.tags {
+            if !room.base.room_tags.tags.contains(tag) {
+                lint.report(
+                    Rule::RequiredTag,
+                    None,
+                    format!("rooms matching `{pattern}` need the tag `{tag}`"),
+                );
+            }
+        }
+        if let Some(min) = requirements
+            .min_entrances
+            .filter(|&min| entrances.min < min)
+        {
+            lint.report(
+                Rule::EntranceCount,
+                None,
+                format!(
+                    "rooms matching `{pattern}` need at least {min} entrances, some variants have {}",
+                    entrances.min
+                ),
+            );
+        }
+        if let Some(max) = requirements
+            .max_entrances
+            .filter(|&max| entrances.max > max)
+        {
+            lint.report(
+                Rule::EntranceCount,
+                None,
+                format!(
+                    "rooms matching `{pattern}` may have at most {max} entrances, some variants have {}",
+                    entrances.max
+                ),
+            );
+        }
+    }
+
+    if room.base.bounds > 0.0 {
+        for outside in room.check_bounds().outside {
+            lint.report(
+                Rule::OutOfBounds,
+                Some(&outside.path),
+                format!(
+                    "reaches {:.0} from the origin, outside the bounds of {:.0}",
+                    outside.reach, room.base.bounds
+                ),
+            );
+        }
+    }
+
+    iter_features(&room.room_features, &mut vec![], &mut |feature, path| {
+        lint.feature(feature, path)
+    });
+    // carving is the slow part, so it is skipped when no rule needs it
+    let space_rules = [
+        Rule::EntranceInRock,
+        Rule::DropPodClearance,
+        Rule::SpawnSnap,
+        Rule::Unreachable,
+    ];
+    if space_rules
+        .iter()
+        .any(|&rule| config.severity(rule).is_some())
+    {
+        lint.space(room, &DensityGrid::carve(room, &CarveSettings::default()));
+    }
+
+    // room wide diagnostics stay first, the rest are ordered by feature
+    lint.diagnostics.sort_by(|a, b| a.path.cmp(&b.path));
+    lint.diagnostics
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        rma::{
+            DropPodCalldownLocationFeature, FVector, FloodFillPillar, RandomSelector,
+            RoomFeatureBase,
+        },
+        voxel::tunnel,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let drop_pod = |z| {
+            RoomFeature::DropPodCalldownLocationFeature(DropPodCalldownLocationFeature {
+                location: FVector {
+                    x: 500.0,
+                    y: 0.0,
+                    z,
+                },
+                ..Default::default()
+            })
+        };
+        let room = RoomGenerator {
+            room_features: vec![
+                tunnel(0.0, 0.0, 2000.0),
+                RoomFeature::RandomSelector(RandomSelector {
+                    base: RoomFeatureBase {
+                        room_features: vec![RoomFeature::FloodFillPillar(FloodFillPillar {
+                            range_scale: FRandRange { min: 2.0, max: 1.0 },
+                            ..Default::default()
+                        })],
+                    },
+                    min: 1,
+                    max: 2,
+                }),
+                drop_pod(-100.0),
+            ],
+            ..Default::default()
+        };
+        let diagnostics = check(&room);
+        let found = diagnostics
+            .iter()
+            .map(|d| (d.rule, d.path.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                (Rule::NoEntrances, None),
+                (Rule::SelectorCount, Some(vec![1])),
+                (Rule::TooFewPoints, Some(vec![1, 0])),
+                (Rule::InvertedRange, Some(vec![1, 0])),
+                (Rule::DropPodClearance, Some(vec![2])),
+            ]
+        );
+
+        let config = Config::parse(
+            r#"
+            [rules]
+            drop-pod-clearance = "off"
+            selector-count = "info"
+
+            [[rooms]]
+            name = "RMA_Start*"
+            tags = ["Room.Start"]
+            "#,
+        )
+        .unwrap();
+        let found = check_with(&room, Some("RMA_Start_01"), &config)
+            .into_iter()
+            .map(|d| (d.rule, d.severity))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                (Rule::NoEntrances, Severity::Error),
+                (Rule::RequiredTag, Severity::Error),
+                (Rule::SelectorCount, Severity::Info),
+                (Rule::TooFewPoints, Severity::Error),
+                (Rule::InvertedRange, Severity::Warning),
+            ]
+        );
+    }
+}
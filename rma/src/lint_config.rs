@@ -0,0 +1,287 @@
+//! Project wide lint configuration and per room suppressions, both stored as TOML.
+//!
+//! The configuration is read from the nearest [`CONFIG_FILE`] in the directory of a room or any
+//! of its parents:
+//!
+//! ```toml
+//! max-bounds = 4000
+//! drop-pod-headroom = 800
+//!
+//! [rules]
+//! duplicate-tag = "off"
+//! out-of-bounds = "error"
+//!
+//! [walk]
+//! height = 200
+//! max-slope = 40
+//!
+//! [[rooms]]
+//! name = "RMA_Start*"
+//! tags = ["Room.Start"]
+//! min-entrances = 1
+//! ```
+//!
+//! Suppressions are kept next to the room, `RMA_Foo.uasset` using `RMA_Foo.lint.toml`:
+//!
+//! ```toml
+//! [[suppress]]
+//! rule = "spawn-snap"
+//! path = "3.0"
+//! reason = "snaps onto a platform spawned at runtime"
+//! ```
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    lint::{Diagnostic, Rule, Severity, DROP_POD_HEADROOM},
+    rma::format_path,
+    snap::MAX_SNAP_DISTANCE,
+    walk::WalkSettings,
+};
+
+/// Name of the project configuration file.
+pub const CONFIG_FILE: &str = "rma-lint.toml";
+
+/// Level a rule is reported at, or `Off` to disable it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// Levels replacing the default severity of rules.
+    pub rules: HashMap<Rule, Level>,
+    /// Largest bounds a room may declare.
+    pub max_bounds: Option<f32>,
+    pub drop_pod_headroom: f32,
+    /// How far spawns are traced when simulating snapping.
+    pub max_snap_distance: f32,
+    /// Requirements for rooms with matching names.
+    pub rooms: Vec<RoomRequirements>,
+    /// Dwarf used to check that entrances and drop pod locations can be walked between.
+    pub walk: WalkSettings,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            rules: HashMap::new(),
+            max_bounds: None,
+            drop_pod_headroom: DROP_POD_HEADROOM,
+            max_snap_distance: MAX_SNAP_DISTANCE,
+            rooms: vec![],
+            walk: WalkSettings::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RoomRequirements {
+    /// Pattern for the room's asset name, `*` matching any run of characters.
+    pub name: String,
+    /// Tags the room must have.
+    pub tags: Vec<String>,
+    /// Fewest entrances any variant of the room may have.
+    pub min_entrances: Option<u32>,
+    /// Most entrances any variant of the room may have.
+    pub max_entrances: Option<u32>,
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).with_context(|| format!("reading {path:?}"))?;
+        Self::parse(&text).with_context(|| format!("parsing {path:?}"))
+    }
+
+    /// Configuration for the room at `room`, or the default if there is none.
+    pub fn discover(room: &Path) -> Result<Self> {
+        let directory = room.parent().unwrap_or(Path::new(""));
+        let absolute = fs::canonicalize(directory).unwrap_or(directory.to_path_buf());
+        match absolute
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .find(|path| path.is_file())
+        {
+            Some(path) => Self::load(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Severity `rule` is reported at, `None` if it is disabled.
+    pub fn severity(&self, rule: Rule) -> Option<Severity> {
+        match self.rules.get(&rule) {
+            None => Some(rule.severity()),
+            Some(Level::Off) => None,
+            Some(Level::Info) => Some(Severity::Info),
+            Some(Level::Warning) => Some(Severity::Warning),
+            Some(Level::Error) => Some(Severity::Error),
+        }
+    }
+
+    /// Requirements applying to the room named `name`.
+    pub fn requirements<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a RoomRequirements> {
+        self.rooms.iter().filter(move |r| glob(&r.name, name))
+    }
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any run of characters.
+fn glob(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        // no `*` at all
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Suppressions {
+    #[serde(default)]
+    pub suppress: Vec<Suppression>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Suppression {
+    pub rule: Rule,
+    /// Dotted path of the feature, suppressing the rule for the whole room if missing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl Suppressions {
+    /// File the suppressions of the room at `room` are stored in.
+    pub fn sidecar(room: &Path) -> PathBuf {
+        room.with_extension("lint.toml")
+    }
+
+    /// Suppressions for the room at `room`, empty if it has no sidecar file.
+    pub fn load(room: &Path) -> Result<Self> {
+        let path = Self::sidecar(room);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = fs::read_to_string(&path).with_context(|| format!("reading {path:?}"))?;
+        toml::from_str(&text).with_context(|| format!("parsing {path:?}"))
+    }
+
+    pub fn save(&self, room: &Path) -> Result<()> {
+        let path = Self::sidecar(room);
+        fs::write(&path, toml::to_string(self)?).with_context(|| format!("writing {path:?}"))
+    }
+
+    pub fn matches(&self, diagnostic: &Diagnostic) -> bool {
+        self.suppress.iter().any(|s| {
+            s.rule == diagnostic.rule
+                && match &s.path {
+                    None => true,
+                    Some(path) => {
+                        diagnostic.path.as_deref().map(format_path).as_ref() == Some(path)
+                    }
+                }
+        })
+    }
+
+    /// Suppress `diagnostic` for its feature, or the whole room if it has none.
+    pub fn add(&mut self, diagnostic: &Diagnostic) {
+        self.suppress.push(Suppression {
+            rule: diagnostic.rule,
+            path: diagnostic.path.as_deref().map(format_path),
+            reason: None,
+        });
+    }
+
+    /// Drop suppressed diagnostics, returning how many there were.
+    pub fn apply(&self, diagnostics: &mut Vec<Diagnostic>) -> usize {
+        let count = diagnostics.len();
+        diagnostics.retain(|d| !self.matches(d));
+        count - diagnostics.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_config() {
+        let config = Config::parse(
+            r#"
+            max-bounds = 4000
+
+            [rules]
+            duplicate-tag = "off"
+            out-of-bounds = "error"
+
+            [walk]
+            height = 200
+
+            [[rooms]]
+            name = "RMA_Start*"
+            tags = ["Room.Start"]
+            min-entrances = 1
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.max_bounds, Some(4000.0));
+        assert_eq!(config.drop_pod_headroom, DROP_POD_HEADROOM);
+        assert_eq!(config.walk.height, 200.0);
+        assert_eq!(config.walk.step, WalkSettings::default().step);
+        assert_eq!(config.severity(Rule::DuplicateTag), None);
+        assert_eq!(config.severity(Rule::OutOfBounds), Some(Severity::Error));
+        assert_eq!(config.severity(Rule::TooFewPoints), Some(Severity::Error));
+        assert_eq!(config.requirements("RMA_Start_02").count(), 1);
+        assert_eq!(config.requirements("RMA_End_02").count(), 0);
+        assert!(Config::parse("unknown = 1").is_err());
+
+        assert!(glob("RMA_*_0*", "RMA_Start_05"));
+        assert!(glob("*Escort*", "RMA_Escort10"));
+        assert!(!glob("RMA_End", "RMA_End_01"));
+
+        let diagnostic = |path| Diagnostic {
+            rule: Rule::SpawnSnap,
+            severity: Severity::Warning,
+            path,
+            message: String::new(),
+        };
+        let mut suppressions = Suppressions::default();
+        suppressions.add(&diagnostic(Some(vec![3, 0])));
+        let text = toml::to_string(&suppressions).unwrap();
+        let suppressions: Suppressions = toml::from_str(&text).unwrap();
+        let mut diagnostics = vec![diagnostic(Some(vec![3, 0])), diagnostic(Some(vec![3]))];
+        assert_eq!(suppressions.apply(&mut diagnostics), 1);
+        assert_eq!(diagnostics[0].path, Some(vec![3]));
+    }
+}
@@ -1,7 +1,7 @@
 #[cfg(target_arch = "wasm32")]
 use crate as rma;
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use log::info;
 use rma::read_rma;
 use rma::AppMode;
@@ -14,40 +14,44 @@ use std::io::Cursor;
 use std::ops::Deref;
 use std::sync::mpsc;
 
-use rma::rma::RoomFeature;
+use rma::bounds::BoundsReport;
+use rma::export::{self, ExportFormat};
+use rma::lint::{self, Severity};
+use rma::lint_config::{Config, Suppressions};
+use rma::mesh::SurfaceMesh;
 use rma::rma::RoomGenerator;
-use rma::room_features::RoomFeatureTrait;
+use rma::rma::{format_path, EItemAdjustmentType, RoomFeature};
+use rma::room_features::{build_bounds, build_snaps, build_walk, RoomFeatureTrait};
+use rma::slice::{self, Slice, SlicePlane};
+use rma::snap::{self, Snap, SnapOutcome, MAX_SNAP_DISTANCE};
+use rma::voxel::{CarveSettings, DensityGrid};
+use rma::walk::{self, Connectivity, Reach};
+use rma::wireframe::ADJUSTMENT_RAY;
 use rma::RMAContext;
 
 // Entry point for non-wasm
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> Result<()> {
-    let path = std::env::args()
-        .nth(1)
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if rma::cli::run(&args)? {
+        return Ok(());
+    }
+
+    let path = args
+        .into_iter()
+        .next()
         .expect("expected path to an RMA .uasset");
 
     run(AppMode::Editor { path })
 }
 
-fn iter_features<F, T>(features: &[RoomFeature], path: &mut Vec<usize>, f: &mut F)
-where
-    F: FnMut(&RoomFeature, &[usize]) -> T,
-{
-    path.push(0);
-    for (i, feat) in features.iter().enumerate() {
-        *path.last_mut().unwrap() = i;
-        f(feat, path);
-        iter_features(&feat.base().room_features, path, f);
-    }
-}
-
 fn build_primitives(
     ctx: &RMAContext,
     rma: &RoomGenerator,
 ) -> HashMap<Vec<usize>, Vec<Box<dyn Object>>> {
     let mut primitives = HashMap::new();
     let mut path = vec![];
-    iter_features(&rma.room_features, &mut path, &mut |f, path| match f {
+    rma::rma::iter_features(&rma.room_features, &mut path, &mut |f, path| match f {
         RoomFeature::FloodFillBox(f) => {
             primitives.insert(path.to_vec(), RoomFeatureTrait::build(f, ctx));
         }
@@ -66,11 +70,164 @@ fn build_primitives(
         RoomFeature::DropPodCalldownLocationFeature(f) => {
             primitives.insert(path.to_vec(), RoomFeatureTrait::build(f, ctx));
         }
-        _ => {}
+        RoomFeature::SpawnTriggerFeature(f) => {
+            primitives.insert(path.to_vec(), RoomFeatureTrait::build(f, ctx));
+        }
+        RoomFeature::ResourceFeature(f) => {
+            primitives.insert(path.to_vec(), RoomFeatureTrait::build(f, ctx));
+        }
+        RoomFeature::RandomSelector(f) => {
+            primitives.insert(path.to_vec(), RoomFeatureTrait::build(f, ctx));
+        }
+        // none of their properties are imported, so there is nothing to draw
+        RoomFeature::FloodFillProceduralPillar
+        | RoomFeature::RandomSubRoomFeature
+        | RoomFeature::SubRoomFeature => {}
     });
     primitives
 }
 
+/// Text drawn next to features, by feature path.
+fn build_labels(rma: &RoomGenerator) -> Vec<(Vec<usize>, Vec3, String)> {
+    let mut labels = vec![];
+    rma::rma::iter_features(&rma.room_features, &mut vec![], &mut |f, path| match f {
+        RoomFeature::EntranceFeature(f) => {
+            labels.push((
+                path.to_vec(),
+                Vec3::from(f.location) + vec3(0.0, 0.0, 150.0),
+                format!("{:?} ({:?})", f.entrance_type, f.priority),
+            ));
+        }
+        RoomFeature::SpawnActorFeature(f) => {
+            if let Some(direction) = f.adjustment_ray() {
+                let target = match f.adjustment {
+                    EItemAdjustmentType::Cieling => "ceiling",
+                    EItemAdjustmentType::Wall => "wall",
+                    _ => "floor",
+                };
+                labels.push((
+                    path.to_vec(),
+                    Vec3::from(f.location) + direction * ADJUSTMENT_RAY,
+                    format!("Snap to {target}"),
+                ));
+            }
+        }
+        _ => {}
+    });
+    labels
+}
+
+/// Position of `p` in the window in egui points, if it is in front of the camera.
+fn screen_position(camera: &Camera, window_height: f32, scale: f32, p: Vec3) -> Option<egui::Pos2> {
+    let clip = camera.projection() * camera.view() * p.extend(1.0);
+    if clip.w <= 0.0 {
+        return None;
+    }
+    let ndc = clip.truncate() / clip.w;
+    let viewport = camera.viewport();
+    let x = viewport.x as f32 + (ndc.x + 1.0) / 2.0 * viewport.width as f32;
+    let y = viewport.y as f32 + (ndc.y + 1.0) / 2.0 * viewport.height as f32;
+    Some(egui::pos2(x / scale, (window_height - y) / scale))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CaveDisplay {
+    Hidden,
+    Translucent,
+    Solid,
+}
+
+fn cave_mesh(rma: &RoomGenerator, smooth: bool) -> SurfaceMesh {
+    SurfaceMesh::carve(rma, &CarveSettings::default(), if smooth { 2 } else { 0 })
+}
+
+fn build_cave(
+    context: &Context,
+    rma: &RoomGenerator,
+    display: CaveDisplay,
+    smooth: bool,
+) -> Option<Gm<Mesh, PhysicalMaterial>> {
+    if display == CaveDisplay::Hidden {
+        return None;
+    }
+    let mesh = cave_mesh(rma, smooth);
+
+    let material = CpuMaterial {
+        albedo: Srgba {
+            r: 160,
+            g: 130,
+            b: 110,
+            a: if display == CaveDisplay::Translucent {
+                100
+            } else {
+                255
+            },
+        },
+        ..Default::default()
+    };
+    let mut material = if display == CaveDisplay::Translucent {
+        PhysicalMaterial::new_transparent(context, &material)
+    } else {
+        PhysicalMaterial::new_opaque(context, &material)
+    };
+    // the surface is usually looked at from inside the cave
+    material.render_states.cull = Cull::None;
+    Some(Gm::new(Mesh::new(context, &(&mesh).into()), material))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SliceMode {
+    Off,
+    Horizontal,
+    Vertical,
+}
+
+/// Quad showing the slice image in place.
+fn build_slice(
+    context: &Context,
+    rma: &RoomGenerator,
+    grid: &DensityGrid,
+    plane: SlicePlane,
+) -> Gm<Mesh, ColorMaterial> {
+    let slice = Slice::new(grid, plane, grid.voxel_size / 2.0);
+    let image = slice.image(&slice::crossings(rma, plane));
+    let texture = CpuTexture {
+        data: TextureData::RgbaU8(
+            image
+                .rgba()
+                .chunks_exact(4)
+                .map(|c| [c[0], c[1], c[2], c[3]])
+                .collect(),
+        ),
+        width: image.width,
+        height: image.height,
+        ..Default::default()
+    };
+    let (w, h) = (slice.width as f32, slice.height as f32);
+    let corners = [vec2(0.0, 0.0), vec2(w, 0.0), vec2(w, h), vec2(0.0, h)];
+    let mesh = CpuMesh {
+        positions: Positions::F32(corners.iter().map(|&c| slice.position(c)).collect()),
+        uvs: Some(corners.iter().map(|c| vec2(c.x / w, c.y / h)).collect()),
+        indices: Indices::U32(vec![0, 1, 2, 0, 2, 3]),
+        ..Default::default()
+    };
+    let mut material = ColorMaterial::new_opaque(
+        context,
+        &CpuMaterial {
+            albedo: Srgba::WHITE,
+            albedo_texture: Some(texture),
+            ..Default::default()
+        },
+    );
+    material.render_states.cull = Cull::None;
+    Gm::new(Mesh::new(context, &mesh), material)
+}
+
+/// Project lint configuration and suppressions for the room at `path`.
+fn load_lint_settings(path: &std::path::Path) -> Result<(Config, Suppressions)> {
+    Ok((Config::discover(path)?, Suppressions::load(path)?))
+}
+
 pub fn run(mode: AppMode) -> Result<()> {
     let mut rma = match &mode {
         AppMode::Editor { path } => {
@@ -118,6 +275,18 @@ pub fn run(mode: AppMode) -> Result<()> {
         },
     );
     wireframe_material.render_states.cull = Cull::Back;
+    let noise_material = PhysicalMaterial::new_transparent(
+        &context,
+        &CpuMaterial {
+            albedo: Srgba {
+                r: 255,
+                g: 140,
+                b: 140,
+                a: 90,
+            },
+            ..Default::default()
+        },
+    );
     let mut wireframe_mesh = CpuMesh::cylinder(10);
     wireframe_mesh
         .transform(&Mat4::from_nonuniform_scale(1.0, 10.0, 10.0))
@@ -126,13 +295,42 @@ pub fn run(mode: AppMode) -> Result<()> {
     let rma_ctx = RMAContext {
         context: &context,
         wireframe_material: wireframe_material.clone(),
+        noise_material: noise_material.clone(),
         wireframe_mesh: wireframe_mesh.clone(),
+        surfaces: false,
     };
 
     let mut primitives = rma.as_ref().map(|rma| build_primitives(&rma_ctx, rma));
+    let mut labels = rma.as_ref().map(build_labels);
 
     let axes = Axes::new(&context, 10., 200.0);
 
+    let mut surfaces = false;
+    let mut cave_display = CaveDisplay::Hidden;
+    let mut smooth_cave = true;
+    let mut cave = None;
+
+    let mut slice_mode = SliceMode::Off;
+    let mut slice_z: f32 = 0.0;
+    let mut slice_origin: [f32; 2] = [0.0, 0.0];
+    let mut slice_yaw: f32 = 0.0;
+    // carved once per room and shared by the slice, snapping and walking overlays
+    let mut carved_grid: Option<DensityGrid> = None;
+    let mut slice_clearance: Vec<std::ops::Range<f32>> = vec![];
+    let mut slice_quad = None;
+
+    let mut show_bounds = false;
+    let mut bounds_report: Option<BoundsReport> = None;
+    let mut bounds_overlay = vec![];
+
+    let mut show_snaps = false;
+    let mut snaps: Vec<Snap> = vec![];
+    let mut snap_overlay = vec![];
+
+    let mut show_walk = false;
+    let mut connectivity = Connectivity::default();
+    let mut walk_overlay = vec![];
+
     let light0 = DirectionalLight::new(&context, 1.0, Srgba::WHITE, &vec3(0.0, -0.5, -0.5));
     let light1 = DirectionalLight::new(&context, 1.0, Srgba::WHITE, &vec3(0.0, 0.5, 0.5));
 
@@ -150,22 +348,89 @@ pub fn run(mode: AppMode) -> Result<()> {
     let mut selected_room = None;
     let (tx, rx) = mpsc::channel();
 
+    // when set, the room is displayed as the concrete variant generated from this seed
+    let mut seed: Option<i32> = None;
+    let mut instance: Option<RoomGenerator> = None;
+    let mut analysis = rma.as_ref().map(|rma| rma.analyze_outcomes());
+    let mut file_status: Option<String> = None;
+
+    // the edited room uses its project's lint configuration, gallery rooms use the defaults
+    let (lint_name, lint_config, mut suppressions) = match &mode {
+        AppMode::Editor { path } => {
+            let path = std::path::Path::new(path);
+            let (config, suppressions) = load_lint_settings(path).unwrap_or_else(|e| {
+                file_status = Some(format!("Lint configuration ignored: {e:#}"));
+                Default::default()
+            });
+            let name = path.file_stem().map(|s| s.to_string_lossy().into_owned());
+            (name, config, suppressions)
+        }
+        AppMode::Gallery { .. } => (None, Config::default(), Suppressions::default()),
+    };
+    // starts out as the dwarf the project lints with
+    let mut walk_settings = lint_config.walk;
+    // lint carves the room and walks it, so it runs on a worker thread and sends back the
+    // diagnostics tagged with its run, results of superseded runs are dropped
+    let (lint_tx, lint_rx) = mpsc::channel();
+    #[cfg(target_arch = "wasm32")]
+    let lint_spawner = spawner.clone();
+    let start_lint = move |rma: &RoomGenerator, run: usize| {
+        let (rma, name, config, tx) = (
+            rma.clone(),
+            lint_name.clone(),
+            lint_config.clone(),
+            lint_tx.clone(),
+        );
+        let check = move || {
+            let diagnostics = lint::check_with(&rma, name.as_deref(), &config);
+            tx.send((run, diagnostics)).ok();
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        std::thread::spawn(check);
+        // the web build has no threads, there the check still blocks a frame
+        #[cfg(target_arch = "wasm32")]
+        lint_spawner.spawn_local(async move { check() }).unwrap();
+    };
+    let (mut diagnostics, mut suppressed) = (vec![], 0);
+    // runs started and the run the shown diagnostics come from
+    let (mut lint_run, mut linted) = (0, 0);
+    if let Some(rma) = &rma {
+        lint_run += 1;
+        start_lint(rma, lint_run);
+    }
+
     let mut task_handles = vec![];
 
     window.render_loop(move |mut frame_input| {
         ex.run_until_stalled();
 
+        let mut rebuild = false;
+        let mut rebuild_cave = false;
+        let mut rebuild_slice = false;
+        let mut rebuild_snaps = false;
+        let mut rebuild_bounds = false;
+        let mut rebuild_walk = false;
+        let mut suppress = None;
+        let mut export = None;
+        let mut import = false;
         if let Ok(new_rma) = rx.try_recv() {
+            analysis = Some(new_rma.analyze_outcomes());
+            lint_run += 1;
+            start_lint(&new_rma, lint_run);
             rma = Some(new_rma);
-            states.clear();
-            primitives = rma.as_ref().map(|rma| build_primitives(&RMAContext {
-                context: &context,
-                wireframe_material: wireframe_material.clone(),
-                wireframe_mesh: wireframe_mesh.clone(),
-            }, rma));
+            rebuild = true;
+        }
+        while let Ok((run, mut found)) = lint_rx.try_recv() {
+            if run == lint_run {
+                suppressed = suppressions.apply(&mut found);
+                diagnostics = found;
+                linted = run;
+            }
         }
 
         let panel_width = 300.0;
+        let window_height = frame_input.viewport.height as f32;
+        let scale = frame_input.device_pixel_ratio;
 
         gui.update(
             &mut frame_input.events,
@@ -180,16 +445,264 @@ pub fn run(mode: AppMode) -> Result<()> {
                     .max_width(panel_width)
                     .show(gui_context, |ui| {
                         use three_d::egui::*;
+                        if let AppMode::Editor { .. } = &mode {
+                            egui::menu::bar(ui, |ui| {
+                                ui.menu_button("File", |ui| {
+                                    ui.menu_button("Export", |ui| {
+                                        for format in ExportFormat::ALL {
+                                            let label = format.extension().to_uppercase();
+                                            if ui.button(label).clicked() {
+                                                export = Some(format);
+                                                ui.close_menu();
+                                            }
+                                        }
+                                    });
+                                    if ui.button("Import glTF edits").clicked() {
+                                        import = true;
+                                        ui.close_menu();
+                                    }
+                                });
+                            });
+                        }
                         ui.heading("Debug Panel");
+                        if let Some(status) = &file_status {
+                            ui.label(status);
+                        }
+                        ui.horizontal(|ui| {
+                            let mut instantiate = seed.is_some();
+                            if ui.checkbox(&mut instantiate, "Variant").changed() {
+                                seed = instantiate.then_some(0);
+                                rebuild = true;
+                            }
+                            if let Some(seed) = &mut seed {
+                                if ui.add(egui::DragValue::new(seed).prefix("seed: ")).changed() {
+                                    rebuild = true;
+                                }
+                                if ui.button("Reroll").clicked() {
+                                    *seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                                    rebuild = true;
+                                }
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Lines and pillars");
+                            for (value, name) in [(false, "Wireframe"), (true, "Surface")] {
+                                if ui.radio_value(&mut surfaces, value, name).changed() {
+                                    rebuild = true;
+                                }
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Cave");
+                            for (display, name) in [
+                                (CaveDisplay::Hidden, "Hidden"),
+                                (CaveDisplay::Translucent, "Translucent"),
+                                (CaveDisplay::Solid, "Solid"),
+                            ] {
+                                if ui.radio_value(&mut cave_display, display, name).changed() {
+                                    rebuild_cave = true;
+                                }
+                            }
+                        });
+                        if ui.checkbox(&mut smooth_cave, "Smooth cave surface").changed() {
+                            rebuild_cave = true;
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label("Slice");
+                            for (mode, name) in [
+                                (SliceMode::Off, "Off"),
+                                (SliceMode::Horizontal, "Horizontal"),
+                                (SliceMode::Vertical, "Vertical"),
+                            ] {
+                                if ui.radio_value(&mut slice_mode, mode, name).changed() {
+                                    rebuild_slice = true;
+                                }
+                            }
+                        });
+                        match slice_mode {
+                            SliceMode::Off => {}
+                            SliceMode::Horizontal => {
+                                let z = egui::DragValue::new(&mut slice_z).prefix("z: ").speed(10.0);
+                                if ui.add(z).changed() {
+                                    rebuild_slice = true;
+                                }
+                            }
+                            SliceMode::Vertical => {
+                                ui.horizontal(|ui| {
+                                    let [x, y] = &mut slice_origin;
+                                    for value in [
+                                        egui::DragValue::new(x).prefix("x: ").speed(10.0),
+                                        egui::DragValue::new(y).prefix("y: ").speed(10.0),
+                                        egui::DragValue::new(&mut slice_yaw).prefix("yaw: "),
+                                    ] {
+                                        if ui.add(value).changed() {
+                                            rebuild_slice = true;
+                                        }
+                                    }
+                                });
+                                if slice_clearance.is_empty() {
+                                    ui.label("No open space at x, y");
+                                }
+                                for span in &slice_clearance {
+                                    ui.label(format!(
+                                        "Open {:.0} to {:.0} ({:.0} clearance)",
+                                        span.start,
+                                        span.end,
+                                        span.end - span.start
+                                    ));
+                                }
+                            }
+                        }
+                        if ui.checkbox(&mut show_bounds, "Bounds").changed() {
+                            rebuild_bounds = true;
+                        }
+                        if let Some(report) = &bounds_report {
+                            ui.label(format!(
+                                "Declared radius {:.0}, features reach {:.0}",
+                                report.declared, report.reach
+                            ));
+                            if let Some(extent) = report.extent {
+                                let size = extent.size();
+                                ui.label(format!(
+                                    "Extent {:.0} x {:.0} x {:.0}",
+                                    size.x, size.y, size.z
+                                ));
+                            }
+                            for o in &report.outside {
+                                ui.label(format!(
+                                    "Feature {} reaches {:.0}, outside the bounds",
+                                    format_path(&o.path),
+                                    o.reach
+                                ));
+                            }
+                        }
+                        if ui.checkbox(&mut show_snaps, "Simulate spawn snapping").changed() {
+                            rebuild_snaps = true;
+                        }
+                        if show_snaps {
+                            let count = |outcome: fn(&SnapOutcome) -> bool| {
+                                snaps.iter().filter(|s| outcome(&s.outcome)).count()
+                            };
+                            ui.label(format!(
+                                "{} snapped, {} missed, {} in rock",
+                                count(|o| matches!(o, SnapOutcome::Hit { .. })),
+                                count(|o| *o == SnapOutcome::Missed),
+                                count(|o| *o == SnapOutcome::InRock),
+                            ));
+                            for snap in &snaps {
+                                let problem = match snap.outcome {
+                                    SnapOutcome::Hit { .. } => continue,
+                                    SnapOutcome::Missed => "never reaches rock",
+                                    SnapOutcome::InRock => "starts in rock",
+                                };
+                                ui.label(format!("Spawn {} {problem}", format_path(&snap.path)));
+                            }
+                        }
+                        if ui.checkbox(&mut show_walk, "Walk between entrances").changed() {
+                            rebuild_walk = true;
+                        }
+                        if show_walk {
+                            ui.horizontal(|ui| {
+                                for value in [
+                                    egui::DragValue::new(&mut walk_settings.height)
+                                        .prefix("height: ")
+                                        .clamp_range(0.0..=1000.0),
+                                    egui::DragValue::new(&mut walk_settings.step)
+                                        .prefix("step: ")
+                                        .clamp_range(0.0..=500.0),
+                                    egui::DragValue::new(&mut walk_settings.max_slope)
+                                        .prefix("slope: ")
+                                        .suffix("°")
+                                        .clamp_range(0.0..=89.0),
+                                ] {
+                                    if ui.add(value).changed() {
+                                        rebuild_walk = true;
+                                    }
+                                }
+                            });
+                            let reached = connectivity
+                                .routes
+                                .iter()
+                                .filter(|r| matches!(r.reach, Reach::Connected { .. }))
+                                .count();
+                            if let Some(root) = connectivity.root.map(|i| &connectivity.routes[i]) {
+                                ui.label(format!(
+                                    "{reached} of {} reachable from the {} at {}",
+                                    connectivity.routes.len(),
+                                    root.kind.name(),
+                                    format_path(&root.path)
+                                ));
+                            }
+                            for route in &connectivity.routes {
+                                let problem = match route.reach {
+                                    Reach::Connected { distance, .. } => {
+                                        ui.label(format!(
+                                            "{} {}: {distance:.0} units",
+                                            route.kind.name(),
+                                            format_path(&route.path)
+                                        ));
+                                        continue;
+                                    }
+                                    Reach::Disconnected => "is cut off",
+                                    Reach::NoFloor => "has no floor",
+                                };
+                                ui.label(format!(
+                                    "{} {} {problem}",
+                                    route.kind.name(),
+                                    format_path(&route.path)
+                                ));
+                            }
+                        }
+                        if let Some(analysis) = &analysis {
+                            ui.label(format!("{} possible variants", analysis.outcomes));
+                            for (name, range) in [
+                                ("Entrances", &analysis.entrances),
+                                ("Spawn actors", &analysis.spawn_actors),
+                                ("Resources", &analysis.resources),
+                            ] {
+                                ui.label(format!(
+                                    "{name}: {}-{} (expected {:.2})",
+                                    range.min, range.max, range.expected
+                                ));
+                            }
+                        }
+                        let lint_header = if linted < lint_run {
+                            "Lint (running)".to_string()
+                        } else if suppressed > 0 {
+                            format!("Lint ({}, {suppressed} suppressed)", diagnostics.len())
+                        } else {
+                            format!("Lint ({})", diagnostics.len())
+                        };
+                        egui::CollapsingHeader::new(lint_header)
+                            .id_source("lint")
+                            .show(ui, |ui| {
+                                if diagnostics.is_empty() {
+                                    ui.label("No problems found");
+                                }
+                                for (i, diagnostic) in diagnostics.iter().enumerate() {
+                                    let color = match diagnostic.severity {
+                                        Severity::Error => Color32::from_rgb(255, 90, 90),
+                                        Severity::Warning => Color32::from_rgb(255, 200, 80),
+                                        Severity::Info => Color32::LIGHT_GRAY,
+                                    };
+                                    ui.horizontal_wrapped(|ui| {
+                                        if let AppMode::Editor { .. } = &mode {
+                                            if ui.small_button("Suppress").clicked() {
+                                                suppress = Some(i);
+                                            }
+                                        }
+                                        ui.colored_label(color, diagnostic.to_string());
+                                    });
+                                }
+                            });
                         fn features(
                             ui: &mut Ui,
                             path: &mut Vec<usize>,
                             f: &[RoomFeature],
                             states: &mut HashMap<Vec<usize>, State>,
                         ) {
-                            path.push(0);
                             for (i, f) in f.iter().enumerate() {
-                                *path.last_mut().unwrap() = i;
+                                path.push(i);
 
                                 let id = ui.make_persistent_id(i);
                                 egui::collapsing_header::CollapsingState::load_with_default_open(
@@ -203,7 +716,8 @@ pub fn run(mode: AppMode) -> Result<()> {
                                         f.name(),
                                     )
                                 })
-                                .body(|ui| features(ui, path, &f.base().room_features, states));
+                                .body(|ui| features(ui, path, f.children(), states));
+                                path.pop();
                             }
                         }
 
@@ -268,7 +782,7 @@ pub fn run(mode: AppMode) -> Result<()> {
                                         ui.group(|ui| {
                                             ui.heading("Room Features");
                                             egui::ScrollArea::vertical().show(ui, |ui| {
-                                                if let Some(rma) = &rma {
+                                                if let Some(rma) = instance.as_ref().or(rma.as_ref()) {
                                                     let mut path = vec![];
                                                     features(
                                                         ui,
@@ -284,9 +798,162 @@ pub fn run(mode: AppMode) -> Result<()> {
                                 });
                             });
                     });
+
+                let painter = gui_context.layer_painter(LayerId::background());
+                for (path, position, text) in labels.iter().flatten() {
+                    if !states.get(path).map_or(true, |s| s.visible) {
+                        continue;
+                    }
+                    if let Some(pos) = screen_position(&camera, window_height, scale, *position) {
+                        painter.text(
+                            pos,
+                            Align2::CENTER_BOTTOM,
+                            text,
+                            FontId::proportional(14.0),
+                            Color32::WHITE,
+                        );
+                    }
+                }
             },
         );
 
+        if let (Some(i), AppMode::Editor { path }) = (suppress, &mode) {
+            let path = std::path::Path::new(path);
+            suppressions.add(&diagnostics[i]);
+            file_status = Some(match suppressions.save(path) {
+                Ok(()) => {
+                    diagnostics.remove(i);
+                    suppressed += 1;
+                    format!("Suppressed in {}", Suppressions::sidecar(path).display())
+                }
+                Err(e) => {
+                    suppressions.suppress.pop();
+                    format!("Suppressing failed: {e:#}")
+                }
+            });
+        }
+
+        if let (true, AppMode::Editor { path }, Some(room)) = (import, &mode, rma.as_mut()) {
+            // paths in the file refer to the exported tree, which differs between variants
+            let input = std::path::Path::new(path).with_extension("glb");
+            file_status = Some(if seed.is_some() {
+                "Turn off Variant to import edits of the room".to_string()
+            } else {
+                match std::fs::read(&input)
+                    .with_context(|| format!("reading {}", input.display()))
+                    .and_then(|data| room.apply_gltf(&data))
+                {
+                    Ok(report) => {
+                        analysis = Some(room.analyze_outcomes());
+                        lint_run += 1;
+                        start_lint(room, lint_run);
+                        rebuild = true;
+                        format!(
+                            "Imported {}: {} changed, {} unmatched, {} added nodes",
+                            input.display(),
+                            report.updated.len(),
+                            report.unmatched.len(),
+                            report.added.len()
+                        )
+                    }
+                    Err(e) => format!("Import failed: {e:#}"),
+                }
+            });
+        }
+
+        let rma_ctx = RMAContext {
+            context: &context,
+            wireframe_material: wireframe_material.clone(),
+            noise_material: noise_material.clone(),
+            wireframe_mesh: wireframe_mesh.clone(),
+            surfaces,
+        };
+        if rebuild {
+            instance = seed.zip(rma.as_ref()).map(|(seed, rma)| rma.instantiate(seed));
+            states.clear();
+            primitives = instance.as_ref().or(rma.as_ref()).map(|rma| build_primitives(&rma_ctx, rma));
+            labels = instance.as_ref().or(rma.as_ref()).map(build_labels);
+        }
+        if rebuild {
+            carved_grid = None;
+            rebuild_slice = true;
+            rebuild_snaps = true;
+            rebuild_bounds = true;
+            rebuild_walk = true;
+        }
+        if rebuild_bounds {
+            let room = instance.as_ref().or(rma.as_ref()).filter(|_| show_bounds);
+            bounds_report = room.map(|room| room.check_bounds());
+            bounds_overlay = match (room, &bounds_report) {
+                (Some(room), Some(report)) => build_bounds(&rma_ctx, &room.base, report),
+                _ => vec![],
+            };
+        }
+        if rebuild_slice {
+            slice_quad = match (slice_mode, instance.as_ref().or(rma.as_ref())) {
+                (SliceMode::Off, _) | (_, None) => None,
+                (mode, Some(rma)) => {
+                    let grid = carved_grid
+                        .get_or_insert_with(|| DensityGrid::carve(rma, &CarveSettings::default()));
+                    let plane = if mode == SliceMode::Horizontal {
+                        SlicePlane::Horizontal { z: slice_z }
+                    } else {
+                        SlicePlane::Vertical {
+                            origin: slice_origin.into(),
+                            yaw: slice_yaw,
+                        }
+                    };
+                    slice_clearance = grid.clearance(slice_origin[0], slice_origin[1]);
+                    Some(build_slice(&context, rma, grid, plane))
+                }
+            };
+        }
+        if rebuild_snaps {
+            snaps = match (show_snaps, instance.as_ref().or(rma.as_ref())) {
+                (true, Some(rma)) => {
+                    let grid = carved_grid
+                        .get_or_insert_with(|| DensityGrid::carve(rma, &CarveSettings::default()));
+                    snap::simulate(rma, grid, MAX_SNAP_DISTANCE)
+                }
+                _ => vec![],
+            };
+            snap_overlay = build_snaps(&rma_ctx, &snaps, MAX_SNAP_DISTANCE);
+        }
+        if rebuild_walk {
+            connectivity = match (show_walk, instance.as_ref().or(rma.as_ref())) {
+                (true, Some(rma)) => {
+                    let grid = carved_grid
+                        .get_or_insert_with(|| DensityGrid::carve(rma, &CarveSettings::default()));
+                    walk::analyze(rma, grid, &walk_settings)
+                }
+                _ => Connectivity::default(),
+            };
+            walk_overlay = build_walk(&rma_ctx, &connectivity);
+        }
+        if rebuild || rebuild_cave {
+            cave = instance
+                .as_ref()
+                .or(rma.as_ref())
+                .and_then(|rma| build_cave(&context, rma, cave_display, smooth_cave));
+        }
+
+        if let (Some(format), AppMode::Editor { path }, Some(rma)) =
+            (export, &mode, instance.as_ref().or(rma.as_ref()))
+        {
+            let path = std::path::Path::new(path);
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let name = match seed {
+                Some(seed) => format!("{stem}_{seed}.{}", format.extension()),
+                None => format!("{stem}.{}", format.extension()),
+            };
+            let output = path.with_file_name(name);
+            let cave = (cave_display != CaveDisplay::Hidden).then(|| cave_mesh(rma, smooth_cave));
+            file_status = Some(match export::save(rma, cave.as_ref(), &output) {
+                Ok(()) => format!("Exported {}", output.display()),
+                Err(e) => format!("Export failed: {e:#}"),
+            });
+        }
+
         let viewport = Viewport {
             x: (panel_width * frame_input.device_pixel_ratio) as i32,
             y: 0,
@@ -320,6 +987,11 @@ pub fn run(mode: AppMode) -> Result<()> {
             .render(
                 &camera,
                 axes.into_iter()
+                    .chain(cave.iter().map(|c| c as &dyn Object))
+                    .chain(slice_quad.iter().map(|s| s as &dyn Object))
+                    .chain(snap_overlay.iter().map(|o| o.deref()))
+                    .chain(walk_overlay.iter().map(|o| o.deref()))
+                    .chain(bounds_overlay.iter().map(|o| o.deref()))
                     .chain(primitives.iter().flatten().flat_map(|(path, p)| {
                         states
                             .get(path)
@@ -348,6 +1020,7 @@ mod test {
 
     use anyhow::Context;
     use rma::read_asset;
+    use rma::rma::iter_features;
 
     use super::*;
 
@@ -365,4 +1038,19 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_read_imported_noise() -> Result<()> {
+        // the pillars' noise override is /Game/Landscape/CSGPillar, an import
+        let asset = read_asset("../assets/rma/RMA_Big05.uasset", EngineVersion::VER_UE4_27)?;
+        let rma = read_rma(asset)?;
+        let mut pillars = vec![];
+        iter_features(&rma.room_features, &mut vec![], &mut |feature, _| {
+            if let RoomFeature::FloodFillPillar(pillar) = feature {
+                pillars.push(pillar.noise_override.is_none());
+            }
+        });
+        assert!(pillars.contains(&true));
+        Ok(())
+    }
 }
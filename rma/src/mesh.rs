@@ -0,0 +1,229 @@
+//! Surface extraction from a [`DensityGrid`] using surface nets, a simple form of dual contouring
+//! placing one vertex in every cell the surface passes through.
+
+use three_d_asset::{vec3, InnerSpace, Vector3};
+
+use crate::{
+    rma::RoomGenerator,
+    voxel::{CarveSettings, DensityGrid},
+};
+
+/// Indexed triangle mesh of the cave surface. Triangles wind counter-clockwise and normals face
+/// into open space.
+#[derive(Debug, Clone, Default)]
+pub struct SurfaceMesh {
+    pub positions: Vec<Vector3<f32>>,
+    pub normals: Vec<Vector3<f32>>,
+    pub indices: Vec<u32>,
+}
+
+impl DensityGrid {
+    /// Extract the boundary between open space and rock.
+    pub fn mesh(&self) -> SurfaceMesh {
+        let mut mesh = SurfaceMesh::default();
+        if self.dims.iter().any(|&d| d < 2) {
+            return mesh;
+        }
+        let cells = self.dims.map(|d| d - 1);
+        let cell_index = |c: [usize; 3]| c[0] + cells[0] * (c[1] + cells[1] * c[2]);
+        let mut cell_vertex = vec![u32::MAX; cells[0] * cells[1] * cells[2]];
+
+        for z in 0..cells[2] {
+            for y in 0..cells[1] {
+                for x in 0..cells[0] {
+                    let corner = |c: usize| [x + (c & 1), y + ((c >> 1) & 1), z + ((c >> 2) & 1)];
+                    let values = [0, 1, 2, 3, 4, 5, 6, 7].map(|c| {
+                        let [x, y, z] = corner(c);
+                        self.get(x, y, z)
+                    });
+
+                    let mut sum = vec3(0.0, 0.0, 0.0);
+                    let mut crossings = 0;
+                    for c in 0..8 {
+                        for bit in [1, 2, 4] {
+                            let other = c | bit;
+                            if c & bit != 0 || (values[c] < 0.0) == (values[other] < 0.0) {
+                                continue;
+                            }
+                            let t = values[c] / (values[c] - values[other]);
+                            let [ax, ay, az] = corner(c);
+                            let [bx, by, bz] = corner(other);
+                            let a = self.position(ax, ay, az);
+                            let b = self.position(bx, by, bz);
+                            sum += a + (b - a) * t;
+                            crossings += 1;
+                        }
+                    }
+                    if crossings > 0 {
+                        cell_vertex[cell_index([x, y, z])] = mesh.positions.len() as u32;
+                        mesh.positions.push(sum / crossings as f32);
+                    }
+                }
+            }
+        }
+
+        for z in 0..self.dims[2] {
+            for y in 0..self.dims[1] {
+                for x in 0..self.dims[0] {
+                    let v = [x, y, z];
+                    let v0 = self.get(x, y, z);
+                    for a in 0..3 {
+                        let (b, c) = ((a + 1) % 3, (a + 2) % 3);
+                        if v[a] + 1 >= self.dims[a]
+                            || v[b] == 0
+                            || v[c] == 0
+                            || v[b] >= cells[b]
+                            || v[c] >= cells[c]
+                        {
+                            continue;
+                        }
+                        let mut n = v;
+                        n[a] += 1;
+                        let v1 = self.get(n[0], n[1], n[2]);
+                        if (v0 < 0.0) == (v1 < 0.0) {
+                            continue;
+                        }
+
+                        let cell = |db: usize, dc: usize| {
+                            let mut p = v;
+                            p[b] = p[b] - 1 + db;
+                            p[c] = p[c] - 1 + dc;
+                            cell_vertex[cell_index(p)]
+                        };
+                        let mut quad = [cell(0, 0), cell(1, 0), cell(1, 1), cell(0, 1)];
+                        // the quad faces +a, flip it if open space is on the -a side
+                        if v0 < 0.0 {
+                            quad.reverse();
+                        }
+                        mesh.indices
+                            .extend([quad[0], quad[1], quad[2], quad[0], quad[2], quad[3]]);
+                    }
+                }
+            }
+        }
+
+        mesh.compute_normals(self);
+        mesh
+    }
+}
+
+impl SurfaceMesh {
+    /// Carve `room` and extract its surface, smoothed `smooth_iterations` times.
+    pub fn carve(room: &RoomGenerator, settings: &CarveSettings, smooth_iterations: usize) -> Self {
+        let grid = DensityGrid::carve(room, settings);
+        let mut mesh = grid.mesh();
+        if smooth_iterations > 0 {
+            mesh.smooth(&grid, smooth_iterations);
+        }
+        mesh
+    }
+
+    /// Normals from the gradient of the density field, pointing into open space.
+    pub fn compute_normals(&mut self, grid: &DensityGrid) {
+        let h = grid.voxel_size / 2.0;
+        self.normals = self
+            .positions
+            .iter()
+            .map(|&p| {
+                let d = |o: Vector3<f32>| grid.sample(p + o) - grid.sample(p - o);
+                let gradient = vec3(
+                    d(vec3(h, 0.0, 0.0)),
+                    d(vec3(0.0, h, 0.0)),
+                    d(vec3(0.0, 0.0, h)),
+                );
+                if gradient.magnitude2() > 0.0 {
+                    -gradient.normalize()
+                } else {
+                    vec3(0.0, 0.0, 1.0)
+                }
+            })
+            .collect();
+    }
+
+    /// Laplacian smoothing, moving every vertex towards the average of its neighbours.
+    /// Normals are recomputed afterwards.
+    pub fn smooth(&mut self, grid: &DensityGrid, iterations: usize) {
+        let mut neighbours = vec![vec![]; self.positions.len()];
+        for tri in self.indices.chunks_exact(3) {
+            for (a, b) in [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])] {
+                neighbours[a as usize].push(b);
+                neighbours[b as usize].push(a);
+            }
+        }
+        for _ in 0..iterations {
+            self.positions = neighbours
+                .iter()
+                .zip(&self.positions)
+                .map(|(n, &p)| {
+                    if n.is_empty() {
+                        return p;
+                    }
+                    let avg = n.iter().fold(vec3(0.0, 0.0, 0.0), |acc, &i| {
+                        acc + self.positions[i as usize]
+                    }) / n.len() as f32;
+                    p + (avg - p) * 0.5
+                })
+                .collect();
+        }
+        self.compute_normals(grid);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::{bounds::Extent, voxel::tunnel};
+
+    use super::*;
+
+    #[test]
+    fn test_mesh_tunnel() {
+        let room = RoomGenerator {
+            room_features: vec![tunnel(0.0, 0.0, 2000.0)],
+            ..Default::default()
+        };
+        let grid = DensityGrid::carve(&room, &CarveSettings::default());
+        let mut mesh = grid.mesh();
+        mesh.smooth(&grid, 2);
+
+        // closed: every edge is used once in each direction
+        let mut edges = HashMap::new();
+        for tri in mesh.indices.chunks_exact(3) {
+            for edge in [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])] {
+                *edges.entry(edge).or_insert(0) += 1;
+            }
+        }
+        assert!(edges
+            .iter()
+            .all(|(&(a, b), &n)| n == 1 && edges.get(&(b, a)) == Some(&1)));
+        // and a single surface without holes, V - E + F = 2
+        let triangles = mesh.indices.len() / 3;
+        assert_eq!(mesh.positions.len() + triangles, edges.len() / 2 + 2);
+
+        for (&p, &n) in mesh.positions.iter().zip(&mesh.normals) {
+            assert!(grid.sample(p + n * 50.0) < grid.sample(p - n * 50.0));
+        }
+        for tri in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.positions[tri[i] as usize]);
+            let n = tri
+                .iter()
+                .fold(vec3(0.0, 0.0, 0.0), |n, &i| n + mesh.normals[i as usize]);
+            assert!((b - a).cross(c - a).dot(n) > 0.0);
+        }
+
+        // the tunnel with its end caps, from the floor to the ceiling, within a voxel
+        let extent = mesh
+            .positions
+            .iter()
+            .map(|&p| Extent { min: p, max: p })
+            .reduce(Extent::union)
+            .unwrap();
+        let expected = Extent {
+            min: vec3(-500.0, -500.0, -100.0),
+            max: vec3(2500.0, 500.0, 400.0),
+        };
+        assert!((extent.min - expected.min).magnitude() < 100.0);
+        assert!((extent.max - expected.max).magnitude() < 100.0);
+    }
+}
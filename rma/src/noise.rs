@@ -0,0 +1,231 @@
+//! CPU evaluation of `UFloodFillSettings` noise.
+//!
+//! The game's terrain noise is fractal gradient noise; this uses Ken Perlin's improved noise with
+//! the reference permutation table (the same one `FMath::PerlinNoise3D` uses) and combines octaves
+//! and layers the way the settings' names suggest. Values will not match the game bit for bit but
+//! have the same frequency, amplitude and range characteristics.
+
+use crate::rma::{FVector, UFloodFillSettings};
+
+#[rustfmt::skip]
+const PERMUTATION: [u8; 256] = [
+    151, 160, 137, 91, 90, 15, 131, 13, 201, 95, 96, 53, 194, 233, 7, 225, 140, 36, 103, 30, 69,
+    142, 8, 99, 37, 240, 21, 10, 23, 190, 6, 148, 247, 120, 234, 75, 0, 26, 197, 62, 94, 252, 219,
+    203, 117, 35, 11, 32, 57, 177, 33, 88, 237, 149, 56, 87, 174, 20, 125, 136, 171, 168, 68, 175,
+    74, 165, 71, 134, 139, 48, 27, 166, 77, 146, 158, 231, 83, 111, 229, 122, 60, 211, 133, 230,
+    220, 105, 92, 41, 55, 46, 245, 40, 244, 102, 143, 54, 65, 25, 63, 161, 1, 216, 80, 73, 209, 76,
+    132, 187, 208, 89, 18, 169, 200, 196, 135, 130, 116, 188, 159, 86, 164, 100, 109, 198, 173,
+    186, 3, 64, 52, 217, 226, 250, 124, 123, 5, 202, 38, 147, 118, 126, 255, 82, 85, 212, 207, 206,
+    59, 227, 47, 16, 58, 17, 182, 189, 28, 42, 223, 183, 170, 213, 119, 248, 152, 2, 44, 154, 163,
+    70, 221, 153, 101, 155, 167, 43, 172, 9, 129, 22, 39, 253, 19, 98, 108, 110, 79, 113, 224, 232,
+    178, 185, 112, 104, 218, 246, 97, 228, 251, 34, 242, 193, 238, 210, 144, 12, 191, 179, 162,
+    241, 81, 51, 145, 235, 249, 14, 239, 107, 49, 192, 214, 31, 181, 199, 106, 157, 184, 84, 204,
+    176, 115, 121, 50, 45, 127, 4, 150, 254, 138, 236, 205, 93, 222, 114, 67, 29, 24, 72, 243, 141,
+    128, 195, 78, 66, 215, 61, 156, 180,
+];
+
+fn perm(i: usize) -> usize {
+    PERMUTATION[i & 255] as usize
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
+
+fn grad(hash: usize, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+/// Improved Perlin noise, roughly in [-1, 1].
+pub fn perlin(x: f32, y: f32, z: f32) -> f32 {
+    let (xf, yf, zf) = (x.floor(), y.floor(), z.floor());
+    let (xi, yi, zi) = (
+        (xf as i32 & 255) as usize,
+        (yf as i32 & 255) as usize,
+        (zf as i32 & 255) as usize,
+    );
+    let (x, y, z) = (x - xf, y - yf, z - zf);
+    let (u, v, w) = (fade(x), fade(y), fade(z));
+
+    let a = perm(xi) + yi;
+    let aa = perm(a) + zi;
+    let ab = perm(a + 1) + zi;
+    let b = perm(xi + 1) + yi;
+    let ba = perm(b) + zi;
+    let bb = perm(b + 1) + zi;
+
+    lerp(
+        w,
+        lerp(
+            v,
+            lerp(u, grad(perm(aa), x, y, z), grad(perm(ba), x - 1.0, y, z)),
+            lerp(
+                u,
+                grad(perm(ab), x, y - 1.0, z),
+                grad(perm(bb), x - 1.0, y - 1.0, z),
+            ),
+        ),
+        lerp(
+            v,
+            lerp(
+                u,
+                grad(perm(aa + 1), x, y, z - 1.0),
+                grad(perm(ba + 1), x - 1.0, y, z - 1.0),
+            ),
+            lerp(
+                u,
+                grad(perm(ab + 1), x, y - 1.0, z - 1.0),
+                grad(perm(bb + 1), x - 1.0, y - 1.0, z - 1.0),
+            ),
+        ),
+    )
+}
+
+impl UFloodFillSettings {
+    /// Evaluate the noise at `pos`, in the range [`min_value`, `max_value`] plus the
+    /// contribution of any `noise_layers`.
+    ///
+    /// `noise_size` is the wavelength of the first octave along each axis. Every further octave
+    /// multiplies the frequency by `freq_multiplier` and the amplitude by
+    /// `amplitude_multiplier`. With `turbulence` each octave contributes its absolute value,
+    /// giving ridged noise.
+    ///
+    /// [`min_value`]: UFloodFillSettings::min_value
+    /// [`max_value`]: UFloodFillSettings::max_value
+    pub fn sample(&self, pos: FVector) -> f32 {
+        let scale = |p: f32, size: f32| if size != 0.0 { p / size } else { 0.0 };
+        let (x, y, z) = (
+            scale(pos.x, self.noise_size.x),
+            scale(pos.y, self.noise_size.y),
+            scale(pos.z, self.noise_size.z),
+        );
+
+        let mut value = 0.0;
+        let mut total = 0.0;
+        let mut frequency = 1.0;
+        let mut amplitude = 1.0;
+        for _ in 0..self.octaves.max(1) {
+            let mut n = perlin(x * frequency, y * frequency, z * frequency);
+            if self.turbulence {
+                n = n.abs() * 2.0 - 1.0;
+            }
+            value += n * amplitude;
+            total += amplitude;
+            frequency *= self.freq_multiplier;
+            amplitude *= self.amplitude_multiplier;
+        }
+        if total != 0.0 {
+            value /= total;
+        }
+        if self.invert {
+            value = -value;
+        }
+        let mut value = self.min_value
+            + (value.clamp(-1.0, 1.0) + 1.0) / 2.0 * (self.max_value - self.min_value);
+
+        for layer in &self.noise_layers {
+            if let Some(noise) = &layer.noise {
+                value += noise.sample(pos) * layer.scale;
+            }
+        }
+        value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        rma::{FloodFillBox, RoomFeature, RoomGenerator},
+        voxel::{CarveSettings, DensityGrid},
+    };
+    use three_d_asset::vec3;
+
+    use super::*;
+
+    fn settings() -> UFloodFillSettings {
+        UFloodFillSettings {
+            noise_size: FVector {
+                x: 300.0,
+                y: 300.0,
+                z: 300.0,
+            },
+            freq_multiplier: 2.0,
+            amplitude_multiplier: 0.5,
+            min_value: -0.5,
+            max_value: 2.0,
+            octaves: 3,
+            ..Default::default()
+        }
+    }
+
+    fn points() -> impl Iterator<Item = FVector> {
+        (0..500).map(|i| FVector {
+            x: i as f32 * 37.3,
+            y: (i % 17) as f32 * 91.1,
+            z: (i % 5) as f32 * -53.7,
+        })
+    }
+
+    #[test]
+    fn test_sample() {
+        let noise = settings();
+        let inverted = UFloodFillSettings {
+            invert: true,
+            ..settings()
+        };
+        let values = points().map(|p| noise.sample(p)).collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            points().map(|p| noise.sample(p)).collect::<Vec<_>>()
+        );
+        assert!(values.iter().all(|v| (-0.5..=2.0).contains(v)));
+        // the noise varies rather than sitting at one end of the range
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        assert!(values.iter().any(|&v| v < mean) && values.iter().any(|&v| v > mean));
+        for (p, v) in points().zip(&values) {
+            // inverting mirrors the value within the range
+            assert!((inverted.sample(p) - (1.5 - v)).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_carve_with_override() {
+        let constant = UFloodFillSettings {
+            min_value: 1.0,
+            max_value: 1.0,
+            ..Default::default()
+        };
+        let carver = FloodFillBox {
+            extends: FVector {
+                x: 200.0,
+                y: 200.0,
+                z: 200.0,
+            },
+            is_carver: true,
+            noise_range: 200.0,
+            noise: Some(constant),
+            ..Default::default()
+        };
+        let room = RoomGenerator {
+            room_features: vec![RoomFeature::FloodFillBox(carver)],
+            ..Default::default()
+        };
+        // the override pushes the walls out by the full noise range everywhere
+        let grid = DensityGrid::carve(&room, &CarveSettings::default());
+        assert!(grid.is_open(vec3(350.0, 0.0, 0.0)));
+        assert!(!grid.is_open(vec3(450.0, 0.0, 0.0)));
+    }
+}
@@ -0,0 +1,176 @@
+//! Port of Unreal Engine's `FRandomStream` (UE 4.27) so room variants can be generated the same
+//! way the game does it.
+//!
+//! # How room generation consumes the stream
+//!
+//! The game owns a single stream per room and draws from it while walking the feature tree depth
+//! first, in the order features are stored in the asset. The mapping below is what
+//! [`RoomGenerator::instantiate`](crate::rma::RoomGenerator::instantiate) reproduces. It has not
+//! been checked against the game, so generated variants may differ from the game's.
+//!
+//! - `RandomSelector`: one [`FRandomStream::rand_range`]`(min, max)` for the number of children,
+//!   clamped to the child count, then one [`FRandomStream::rand_helper`]`(remaining)` per pick,
+//!   removing each picked child from the candidate list. Picked children keep their original
+//!   order and are then generated recursively.
+//! - `FloodFillPillar`: `range_scale`, `noise_range_scale` and `endcap_scale` are each sampled
+//!   with [`FRandRange::sample`] in that order, followed by `range`, `noise_range`, `skew_factor`
+//!   and `fill_amount` of every `FRandLinePoint` in point order.
+//! - `SpawnActorFeature`: a single fraction lerps between `scale_min` and `scale_max` (keeping the
+//!   scale proportional), then pitch, yaw and roll are each sampled from
+//!   `-rotation_delta..rotation_delta`.
+
+use crate::rma::{FRandRange, FRotator, FVector, SpawnActorFeature};
+
+/// Deterministic random number generator matching `FRandomStream`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FRandomStream {
+    initial_seed: i32,
+    seed: u32,
+}
+
+impl FRandomStream {
+    pub fn new(seed: i32) -> Self {
+        Self {
+            initial_seed: seed,
+            seed: seed as u32,
+        }
+    }
+    /// Restart the sequence from the initial seed.
+    pub fn reset(&mut self) {
+        self.seed = self.initial_seed as u32;
+    }
+    pub fn initial_seed(&self) -> i32 {
+        self.initial_seed
+    }
+    pub fn current_seed(&self) -> i32 {
+        self.seed as i32
+    }
+    fn mutate_seed(&mut self) {
+        self.seed = self.seed.wrapping_mul(196314165).wrapping_add(907633515);
+    }
+    pub fn get_unsigned_int(&mut self) -> u32 {
+        self.mutate_seed();
+        self.seed
+    }
+    /// Random float in [0, 1), built by placing the top 23 bits of the seed in the mantissa.
+    pub fn get_fraction(&mut self) -> f32 {
+        self.mutate_seed();
+        f32::from_bits(0x3F800000 | (self.seed >> 9)) - 1.0
+    }
+    pub fn frand(&mut self) -> f32 {
+        self.get_fraction()
+    }
+    /// Random integer in [0, a), or 0 if `a` is not positive.
+    pub fn rand_helper(&mut self, a: i32) -> i32 {
+        if a > 0 {
+            ((self.get_fraction() * a as f32).trunc() as i32).min(a - 1)
+        } else {
+            0
+        }
+    }
+    /// Random integer in [min, max].
+    pub fn rand_range(&mut self, min: i32, max: i32) -> i32 {
+        let range = max - min + 1;
+        min + self.rand_helper(range)
+    }
+    /// Random float in [min, max).
+    pub fn frand_range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.frand()
+    }
+    /// Random unit vector, uniformly distributed on the sphere.
+    pub fn vrand(&mut self) -> FVector {
+        loop {
+            let x = self.get_fraction() * 2.0 - 1.0;
+            let y = self.get_fraction() * 2.0 - 1.0;
+            let z = self.get_fraction() * 2.0 - 1.0;
+            let l = x * x + y * y + z * z;
+            if (1e-4..=1.0).contains(&l) {
+                let l = l.sqrt();
+                return FVector {
+                    x: x / l,
+                    y: y / l,
+                    z: z / l,
+                };
+            }
+        }
+    }
+}
+
+impl FRandRange {
+    pub fn sample(&self, stream: &mut FRandomStream) -> f32 {
+        stream.frand_range(self.min, self.max)
+    }
+}
+
+impl SpawnActorFeature {
+    pub fn sample_scale(&self, stream: &mut FRandomStream) -> FVector {
+        let t = stream.frand();
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        FVector {
+            x: lerp(self.scale_min.x, self.scale_max.x),
+            y: lerp(self.scale_min.y, self.scale_max.y),
+            z: lerp(self.scale_min.z, self.scale_max.z),
+        }
+    }
+    pub fn sample_rotation(&self, stream: &mut FRandomStream) -> FRotator {
+        let d = self.rotation_delta;
+        FRotator {
+            pitch: stream.frand_range(-d.pitch, d.pitch),
+            yaw: stream.frand_range(-d.yaw, d.yaw),
+            roll: stream.frand_range(-d.roll, d.roll),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // The sequences below were recorded from this port rather than the engine, so they only
+    // guard against accidental changes.
+
+    #[test]
+    fn test_unsigned_int_regression() {
+        let mut stream = FRandomStream::new(0);
+        let seq = (0..4)
+            .map(|_| stream.get_unsigned_int())
+            .collect::<Vec<_>>();
+        assert_eq!(seq, [907633515, 2641306770, 4111285669, 4143921812]);
+
+        let mut stream = FRandomStream::new(-1);
+        let seq = (0..4)
+            .map(|_| stream.get_unsigned_int())
+            .collect::<Vec<_>>();
+        assert_eq!(seq, [711319350, 3909775769, 3637562392, 2136474723]);
+    }
+
+    #[test]
+    fn test_fraction_regression() {
+        let mut stream = FRandomStream::new(1234);
+        let seq = (0..4).map(|_| stream.get_fraction()).collect::<Vec<_>>();
+        assert_eq!(seq, [0.61493933, 0.1673255, 0.064095855, 0.73050606]);
+    }
+
+    #[test]
+    fn test_rand_range_regression() {
+        let mut stream = FRandomStream::new(0);
+        let seq = (0..8).map(|_| stream.rand_range(0, 10)).collect::<Vec<_>>();
+        assert_eq!(seq, [2, 6, 10, 10, 2, 2, 6, 0]);
+
+        stream.reset();
+        assert_eq!(stream.current_seed(), 0);
+        assert_eq!(stream.rand_range(5, 15), 7);
+        assert_eq!(stream.rand_helper(0), 0);
+    }
+
+    #[test]
+    fn test_rand_helper_upper_bound() {
+        // the first mutation of this seed is u32::MAX, the largest fraction
+        let mut stream = FRandomStream::new(1933857988);
+        assert_eq!(stream.get_unsigned_int(), u32::MAX);
+        stream.reset();
+        assert_eq!(stream.rand_helper(10), 9);
+        stream.reset();
+        assert_eq!(stream.rand_helper(i32::MAX), i32::MAX - 255);
+    }
+}
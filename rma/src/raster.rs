@@ -0,0 +1,464 @@
+//! Software rasterizer rendering shaded views of a room to images, without a GPU.
+//!
+//! Feature outlines and point feature markers are drawn opaque and the cave surface is blended
+//! over them, much like the viewer's translucent cave, while covering the background fully.
+
+use std::io::Write;
+
+use anyhow::Result;
+use three_d_asset::{vec2, vec3, InnerSpace, Srgba, Vector2, Vector3};
+
+use crate::{
+    export::marker,
+    mesh::SurfaceMesh,
+    rma::{iter_features, RoomFeature, RoomGenerator},
+    wireframe::{Segment, Wireframe},
+};
+
+/// Perspective camera in room space.
+#[derive(Debug, Clone, Copy)]
+pub struct Camera {
+    pub position: Vector3<f32>,
+    pub target: Vector3<f32>,
+    /// Vertical field of view in degrees.
+    pub fov: f32,
+}
+
+impl Camera {
+    /// Camera looking at a sphere from above at an angle, fitting it in view.
+    pub fn fit(center: Vector3<f32>, radius: f32, fov: f32) -> Self {
+        // a degenerate field of view would put the camera at infinity or on the target
+        let distance = radius.max(100.0) / (fov.clamp(1.0, 179.0).to_radians() / 2.0).sin();
+        Self {
+            position: center + vec3(-0.6, -0.5, 0.62).normalize() * distance,
+            target: center,
+            fov,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    pub width: u32,
+    pub height: u32,
+    /// Camera to render from, fitted to the room when unset.
+    pub camera: Option<Camera>,
+    /// Vertical field of view in degrees of the camera fitted to the room.
+    pub fov: f32,
+    /// Cave surface blended over the features.
+    pub cave: Option<SurfaceMesh>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            width: 512,
+            height: 512,
+            camera: None,
+            fov: 45.0,
+            cave: None,
+        }
+    }
+}
+
+const BACKGROUND: [f32; 3] = [0.0, 0.0, 0.0];
+const CAVE_COLOR: [f32; 3] = [0.63, 0.51, 0.43];
+const CAVE_ALPHA: f32 = 0.6;
+const WIREFRAME_COLOR: [f32; 3] = [1.0, 0.0, 0.0];
+
+fn rgb(c: Srgba) -> [f32; 3] {
+    [c.r, c.g, c.b].map(|c| c as f32 / 255.0)
+}
+
+/// Point projected to the screen, with the inverse of its view depth for perspective correct
+/// interpolation.
+#[derive(Clone, Copy)]
+struct Projected {
+    screen: Vector2<f32>,
+    inv_depth: f32,
+}
+
+struct View {
+    position: Vector3<f32>,
+    right: Vector3<f32>,
+    up: Vector3<f32>,
+    forward: Vector3<f32>,
+    focal: f32,
+    center: Vector2<f32>,
+}
+
+/// Closest distance in front of the camera that is drawn.
+const NEAR: f32 = 10.0;
+
+impl View {
+    fn new(camera: &Camera, width: u32, height: u32) -> Self {
+        let forward = camera.target - camera.position;
+        // a camera on its target has no direction, look along X rather than produce NaN
+        let forward = if forward.magnitude2() > 0.0 {
+            forward.normalize()
+        } else {
+            vec3(1.0, 0.0, 0.0)
+        };
+        let world_up = if forward.z.abs() > 0.999 {
+            vec3(1.0, 0.0, 0.0)
+        } else {
+            vec3(0.0, 0.0, 1.0)
+        };
+        // UE is left handed, so right is up × forward rather than forward × up
+        let right = world_up.cross(forward).normalize();
+        Self {
+            position: camera.position,
+            right,
+            up: forward.cross(right),
+            forward,
+            focal: height as f32 / 2.0 / (camera.fov.to_radians() / 2.0).tan(),
+            center: vec2(width as f32, height as f32) / 2.0,
+        }
+    }
+
+    fn depth(&self, p: Vector3<f32>) -> f32 {
+        (p - self.position).dot(self.forward)
+    }
+
+    fn project(&self, p: Vector3<f32>) -> Option<Projected> {
+        (self.depth(p) >= NEAR).then(|| self.project_unclipped(p))
+    }
+
+    fn project_unclipped(&self, p: Vector3<f32>) -> Projected {
+        let d = p - self.position;
+        let depth = d.dot(self.forward);
+        Projected {
+            screen: self.center + vec2(d.dot(self.right), -d.dot(self.up)) * (self.focal / depth),
+            inv_depth: 1.0 / depth,
+        }
+    }
+}
+
+/// Color and depth buffer.
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    color: Vec<[f32; 3]>,
+    /// Inverse view depth, larger is closer. Zero where nothing was drawn.
+    depth: Vec<f32>,
+}
+
+impl Image {
+    pub(crate) fn new(width: u32, height: u32) -> Self {
+        let len = (width * height) as usize;
+        Self {
+            width,
+            height,
+            color: vec![BACKGROUND; len],
+            depth: vec![0.0; len],
+        }
+    }
+
+    /// Overwrite a pixel, ignoring depth. Pixels outside the image are skipped.
+    pub(crate) fn set(&mut self, x: i32, y: i32, color: [f32; 3]) {
+        if x >= 0 && y >= 0 && x < self.width as i32 && y < self.height as i32 {
+            self.color[(y as u32 * self.width + x as u32) as usize] = color;
+        }
+    }
+
+    fn plot(&mut self, x: i32, y: i32, inv_depth: f32, color: [f32; 3]) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        let i = (y as u32 * self.width + x as u32) as usize;
+        if inv_depth > self.depth[i] {
+            self.depth[i] = inv_depth;
+            self.color[i] = color;
+        }
+    }
+
+    fn triangle(
+        &mut self,
+        view: &View,
+        vertices: [(Vector3<f32>, Vector3<f32>); 3],
+        color: [f32; 3],
+    ) {
+        let Some(p) = vertices
+            .iter()
+            .map(|&(p, _)| view.project(p))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return;
+        };
+        let (a, b, c) = (p[0].screen, p[1].screen, p[2].screen);
+        let area = (b - a).perp_dot(c - a);
+        if area.abs() < 1e-6 {
+            return;
+        }
+        let lo = vec2(a.x.min(b.x).min(c.x), a.y.min(b.y).min(c.y));
+        let hi = vec2(a.x.max(b.x).max(c.x), a.y.max(b.y).max(c.y));
+        let (x0, y0) = (lo.x.floor().max(0.0) as i32, lo.y.floor().max(0.0) as i32);
+        let x1 = hi.x.ceil().min(self.width as f32 - 1.0) as i32;
+        let y1 = hi.y.ceil().min(self.height as f32 - 1.0) as i32;
+
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let s = vec2(x as f32 + 0.5, y as f32 + 0.5);
+                let w = [
+                    (c - b).perp_dot(s - b) / area,
+                    (a - c).perp_dot(s - c) / area,
+                    (b - a).perp_dot(s - a) / area,
+                ];
+                if w.iter().any(|&w| w < 0.0) {
+                    continue;
+                }
+                let inv_depth = (0..3).map(|i| w[i] * p[i].inv_depth).sum::<f32>();
+                let normal = (0..3)
+                    .map(|i| vertices[i].1 * (w[i] * p[i].inv_depth / inv_depth))
+                    .fold(vec3(0.0, 0.0, 0.0), |a, n| a + n);
+                // headlight with some ambient, lighting both sides
+                let light = if normal.magnitude2() > 0.0 {
+                    normal.normalize().dot(view.forward).abs()
+                } else {
+                    1.0
+                };
+                let shade = 0.25 + 0.75 * light;
+                self.plot(x, y, inv_depth, color.map(|c| c * shade));
+            }
+        }
+    }
+
+    fn mesh(&mut self, view: &View, mesh: &SurfaceMesh, color: [f32; 3]) {
+        for tri in mesh.indices.chunks_exact(3) {
+            let vertex = |i: u32| {
+                let i = i as usize;
+                let normal = mesh.normals.get(i).copied().unwrap_or(vec3(0.0, 0.0, 0.0));
+                (mesh.positions[i], normal)
+            };
+            self.triangle(
+                view,
+                [vertex(tri[0]), vertex(tri[1]), vertex(tri[2])],
+                color,
+            );
+        }
+    }
+
+    fn segment(&mut self, view: &View, [a, b]: Segment, color: [f32; 3]) {
+        // clip to the near plane in view space
+        let (depth_a, depth_b) = (view.depth(a), view.depth(b));
+        if depth_a < NEAR && depth_b < NEAR {
+            return;
+        }
+        let at_near = |from: Vector3<f32>, to: Vector3<f32>, from_depth: f32, to_depth: f32| {
+            from + (to - from) * ((NEAR - from_depth) / (to_depth - from_depth))
+        };
+        let (a, b) = (
+            if depth_a < NEAR {
+                at_near(a, b, depth_a, depth_b)
+            } else {
+                a
+            },
+            if depth_b < NEAR {
+                at_near(b, a, depth_b, depth_a)
+            } else {
+                b
+            },
+        );
+        let (a, b) = (view.project_unclipped(a), view.project_unclipped(b));
+
+        // then to the image in screen space, where inverse depth is linear, so points close to
+        // the near plane do not take millions of steps
+        let d = b.screen - a.screen;
+        let (mut t0, mut t1) = (0.0f32, 1.0f32);
+        for (p, q) in [
+            (-d.x, a.screen.x),
+            (d.x, self.width as f32 - a.screen.x),
+            (-d.y, a.screen.y),
+            (d.y, self.height as f32 - a.screen.y),
+        ] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return;
+                }
+            } else if p < 0.0 {
+                t0 = t0.max(q / p);
+            } else {
+                t1 = t1.min(q / p);
+            }
+        }
+        if t0 > t1 {
+            return;
+        }
+
+        let steps = ((t1 - t0) * d.x.abs().max(d.y.abs())).ceil().max(1.0) as i32;
+        for i in 0..=steps {
+            let t = t0 + (t1 - t0) * i as f32 / steps as f32;
+            let s = a.screen + d * t;
+            let inv_depth = a.inv_depth + (b.inv_depth - a.inv_depth) * t;
+            self.plot(s.x.floor() as i32, s.y.floor() as i32, inv_depth, color);
+        }
+    }
+
+    /// Blend `layer` over this image wherever it is in front, fully covering the background.
+    fn blend(&mut self, layer: &Image, alpha: f32) {
+        for i in 0..self.color.len() {
+            if layer.depth[i] > 0.0 && layer.depth[i] >= self.depth[i] {
+                let alpha = if self.depth[i] > 0.0 { alpha } else { 1.0 };
+                let (under, over) = (self.color[i], layer.color[i]);
+                self.color[i] = [0, 1, 2].map(|c| under[c] + (over[c] - under[c]) * alpha);
+            }
+        }
+    }
+
+    /// 8 bit RGBA pixels, row by row from the top.
+    pub fn rgba(&self) -> Vec<u8> {
+        self.color
+            .iter()
+            .flat_map(|c| {
+                let [r, g, b] = c.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+                [r, g, b, 255]
+            })
+            .collect()
+    }
+
+    pub fn write_png<W: Write>(&self, w: W) -> Result<()> {
+        let mut encoder = png::Encoder::new(w, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.rgba())?;
+        Ok(())
+    }
+}
+
+/// Bounding sphere of the room's feature geometry and cave.
+fn bounds(room: &RoomGenerator, cave: Option<&SurfaceMesh>) -> (Vector3<f32>, f32) {
+    let mut points = vec![];
+    iter_features(
+        &room.room_features,
+        &mut vec![],
+        &mut |feature, _| match feature {
+            RoomFeature::FloodFillLine(f) => points.extend(f.wireframe().into_iter().flatten()),
+            RoomFeature::FloodFillPillar(f) => points.extend(f.wireframe().into_iter().flatten()),
+            RoomFeature::FloodFillBox(f) => points.extend(f.wireframe().into_iter().flatten()),
+            _ => points.extend(feature.location().map(Vector3::from)),
+        },
+    );
+    points.extend(cave.iter().flat_map(|c| c.positions.iter().copied()));
+    let Some(&first) = points.first() else {
+        return (vec3(0.0, 0.0, 0.0), 1000.0);
+    };
+    let (lo, hi) = points.iter().fold((first, first), |(lo, hi), p| {
+        (
+            vec3(lo.x.min(p.x), lo.y.min(p.y), lo.z.min(p.z)),
+            vec3(hi.x.max(p.x), hi.y.max(p.y), hi.z.max(p.z)),
+        )
+    });
+    ((lo + hi) / 2.0, (hi - lo).magnitude() / 2.0)
+}
+
+impl RoomGenerator {
+    /// Render the room's feature outlines, markers and optionally its cave surface.
+    pub fn render(&self, options: &RenderOptions) -> Image {
+        let camera = options.camera.unwrap_or_else(|| {
+            let (center, radius) = bounds(self, options.cave.as_ref());
+            Camera::fit(center, radius, options.fov)
+        });
+        let view = View::new(&camera, options.width, options.height);
+
+        let mut image = Image::new(options.width, options.height);
+        iter_features(&self.room_features, &mut vec![], &mut |feature, _| {
+            let (segments, color) = match feature {
+                RoomFeature::FloodFillLine(f) => (f.wireframe(), WIREFRAME_COLOR),
+                RoomFeature::FloodFillPillar(f) => (f.wireframe(), WIREFRAME_COLOR),
+                RoomFeature::FloodFillBox(f) => (f.wireframe(), WIREFRAME_COLOR),
+                RoomFeature::EntranceFeature(f) => (f.wireframe(), rgb(f.entrance_type.color())),
+                _ => (vec![], WIREFRAME_COLOR),
+            };
+            for segment in segments {
+                image.segment(&view, segment, color);
+            }
+            let color = match feature {
+                RoomFeature::EntranceFeature(f) => rgb(f.entrance_type.color()),
+                RoomFeature::SpawnActorFeature(_) => [1.0, 0.78, 0.0],
+                RoomFeature::DropPodCalldownLocationFeature(_) => [0.0, 1.0, 0.0],
+                _ => [0.7, 0.7, 0.7],
+            };
+            if let Some(location) = feature.location() {
+                image.mesh(&view, &marker(location.into()), color);
+            }
+        });
+
+        if let Some(cave) = &options.cave {
+            let mut layer = Image::new(options.width, options.height);
+            layer.mesh(&view, cave, CAVE_COLOR);
+            image.blend(&layer, CAVE_ALPHA);
+        }
+        image
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Pixels that are not background.
+    fn covered(image: &Image) -> Vec<(u32, u32)> {
+        (0..image.height)
+            .flat_map(|y| (0..image.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| image.color[(y * image.width + x) as usize] != BACKGROUND)
+            .collect()
+    }
+
+    #[test]
+    fn test_segment() {
+        // 50 px focal length, looking along X with Y to the right
+        let camera = Camera {
+            position: vec3(-1000.0, 0.0, 0.0),
+            target: vec3(0.0, 0.0, 0.0),
+            fov: 90.0,
+        };
+        let view = View::new(&camera, 100, 100);
+
+        let mut image = Image::new(100, 100);
+        image.segment(
+            &view,
+            [vec3(0.0, -500.0, 0.0), vec3(0.0, 500.0, 0.0)],
+            WIREFRAME_COLOR,
+        );
+        assert_eq!(
+            covered(&image),
+            (25..=75).map(|x| (x, 50)).collect::<Vec<_>>()
+        );
+
+        // starts behind the near plane and projects far off screen, only the visible part of
+        // the column is drawn
+        let mut image = Image::new(100, 100);
+        image.segment(
+            &view,
+            [vec3(-995.0, 0.0, 100.0), vec3(1000.0, 0.0, 100.0)],
+            WIREFRAME_COLOR,
+        );
+        assert_eq!(
+            covered(&image),
+            (0..=47).map(|y| (50, y)).collect::<Vec<_>>()
+        );
+
+        // entirely behind the camera
+        let mut image = Image::new(100, 100);
+        image.segment(
+            &view,
+            [vec3(-2000.0, -500.0, 0.0), vec3(-2000.0, 500.0, 0.0)],
+            WIREFRAME_COLOR,
+        );
+        assert!(covered(&image).is_empty());
+    }
+
+    #[test]
+    fn test_camera_on_target() {
+        let camera = Camera {
+            position: vec3(0.0, 0.0, 0.0),
+            target: vec3(0.0, 0.0, 0.0),
+            fov: 45.0,
+        };
+        let view = View::new(&camera, 100, 100);
+        assert!(view.right.x.is_finite() && view.up.z.is_finite());
+
+        let camera = Camera::fit(vec3(0.0, 0.0, 0.0), 1000.0, 0.0);
+        assert!(camera.position.x.is_finite());
+    }
+}
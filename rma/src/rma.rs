@@ -12,12 +12,16 @@ use unreal_asset::Asset;
 
 use std::io::{Read, Seek};
 
-#[derive(Debug, Default, Serialize, FromExport, FromProperties)]
+/// Indices leading to a feature through the nested `room_features` lists, starting at the
+/// `RoomGenerator`.
+pub type FeaturePath = Vec<usize>;
+
+#[derive(Debug, Clone, Default, Serialize, FromExport, FromProperties)]
 pub struct RoomFeatureBase {
     pub room_features: Vec<RoomFeature>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub enum RoomFeature {
     FloodFillBox(FloodFillBox),
     FloodFillProceduralPillar,
@@ -66,6 +70,64 @@ impl RoomFeature {
             RoomFeature::DropPodCalldownLocationFeature(f) => &f.base,
         }
     }
+    /// Child features, empty for feature types that are not imported.
+    pub fn children(&self) -> &[RoomFeature] {
+        match self {
+            RoomFeature::FloodFillProceduralPillar
+            | RoomFeature::RandomSubRoomFeature
+            | RoomFeature::SubRoomFeature => &[],
+            _ => &self.base().room_features,
+        }
+    }
+    /// Mutable base, `None` for feature types that are not imported.
+    pub fn base_mut(&mut self) -> Option<&mut RoomFeatureBase> {
+        match self {
+            RoomFeature::FloodFillBox(f) => Some(&mut f.base),
+            RoomFeature::SpawnTriggerFeature(f) => Some(&mut f.base),
+            RoomFeature::FloodFillPillar(f) => Some(&mut f.base),
+            RoomFeature::RandomSelector(f) => Some(&mut f.base),
+            RoomFeature::EntranceFeature(f) => Some(&mut f.base),
+            RoomFeature::SpawnActorFeature(f) => Some(&mut f.base),
+            RoomFeature::FloodFillLine(f) => Some(&mut f.base),
+            RoomFeature::ResourceFeature(f) => Some(&mut f.base),
+            RoomFeature::DropPodCalldownLocationFeature(f) => Some(&mut f.base),
+            RoomFeature::FloodFillProceduralPillar
+            | RoomFeature::RandomSubRoomFeature
+            | RoomFeature::SubRoomFeature => None,
+        }
+    }
+    /// Location of features placed at a single point.
+    pub fn location(&self) -> Option<FVector> {
+        match self {
+            RoomFeature::SpawnTriggerFeature(f) => Some(f.transform.translation),
+            RoomFeature::EntranceFeature(f) => Some(f.location),
+            RoomFeature::SpawnActorFeature(f) => Some(f.location),
+            RoomFeature::ResourceFeature(f) => Some(f.location),
+            RoomFeature::DropPodCalldownLocationFeature(f) => Some(f.location),
+            _ => None,
+        }
+    }
+}
+
+/// Call `f` for every feature in `features` and their children, parents first.
+pub fn iter_features<F, T>(features: &[RoomFeature], path: &mut FeaturePath, f: &mut F)
+where
+    F: FnMut(&RoomFeature, &[usize]) -> T,
+{
+    for (i, feat) in features.iter().enumerate() {
+        path.push(i);
+        f(feat, path);
+        iter_features(feat.children(), path, f);
+        path.pop();
+    }
+}
+
+/// Dotted form of a feature path, e.g. `0.2.1`.
+pub fn format_path(path: &[usize]) -> String {
+    path.iter()
+        .map(usize::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
 }
 
 impl<C: Seek + Read> FromExport<C> for RoomFeature {
@@ -111,11 +173,11 @@ impl<C: Seek + Read> FromExport<C> for RoomFeature {
     }
 }
 
-#[derive(Debug, Default, Serialize, FromExport, FromProperties)]
+#[derive(Debug, Clone, Default, Serialize, FromExport, FromProperties)]
 pub struct FloodFillBox {
     #[serde(flatten)]
     pub base: RoomFeatureBase,
-    pub noise: (), // TODO import Option<UFloodFillSettings>,
+    pub noise: Option<UFloodFillSettings>,
     pub position: FVector,
     pub extends: FVector,
     pub rotation: FRotator,
@@ -123,7 +185,7 @@ pub struct FloodFillBox {
     pub noise_range: f32,
 }
 
-#[derive(Debug, Default, Serialize, FromExport, FromProperties)]
+#[derive(Debug, Clone, Default, Serialize, FromExport, FromProperties)]
 pub struct SpawnTriggerFeature {
     #[serde(flatten)]
     pub base: RoomFeatureBase,
@@ -132,13 +194,13 @@ pub struct SpawnTriggerFeature {
     pub message: FName,
 }
 
-#[derive(Debug, Default, Serialize, FromProperty, FromProperties)]
+#[derive(Debug, Clone, Default, Serialize, FromProperty, FromProperties)]
 pub struct FRandRange {
     pub min: f32,
     pub max: f32,
 }
 
-#[derive(Debug, Default, Serialize, FromProperty, FromProperties)]
+#[derive(Debug, Clone, Default, Serialize, FromProperty, FromProperties)]
 pub struct FRandLinePoint {
     pub location: FVector,
     pub range: FRandRange,
@@ -147,18 +209,18 @@ pub struct FRandLinePoint {
     pub fill_amount: FRandRange,
 }
 
-#[derive(Debug, Default, Serialize, FromExport, FromProperties)]
+#[derive(Debug, Clone, Default, Serialize, FromExport, FromProperties)]
 pub struct FloodFillPillar {
     #[serde(flatten)]
     pub base: RoomFeatureBase,
-    pub noise_override: (), // Option<UFloodFillSettings>,
+    pub noise_override: Option<UFloodFillSettings>,
     pub points: Vec<FRandLinePoint>,
     pub range_scale: FRandRange,
     pub noise_range_scale: FRandRange,
     pub endcap_scale: FRandRange,
 }
 
-#[derive(Debug, Default, Serialize, FromExport, FromProperties)]
+#[derive(Debug, Clone, Default, Serialize, FromExport, FromProperties)]
 pub struct RandomSelector {
     #[serde(flatten)]
     pub base: RoomFeatureBase,
@@ -235,7 +297,7 @@ impl<C: Read + Seek> FromProperty<C> for FQuat {
                     x: property.value.x.0 as f32,
                     y: property.value.y.0 as f32,
                     z: property.value.z.0 as f32,
-                    w: property.value.z.0 as f32,
+                    w: property.value.w.0 as f32,
                 }),
                 _ => bail!("{property:?}"),
             },
@@ -256,7 +318,7 @@ impl<C: Read + Seek> FromProperty<C> for FName {
     }
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub enum ECaveEntranceType {
     #[default]
     EntranceAndExit,
@@ -281,7 +343,7 @@ impl<C: Read + Seek> FromProperty<C> for ECaveEntranceType {
     }
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub enum ECaveEntrancePriority {
     #[default]
     Primary,
@@ -303,7 +365,7 @@ impl<C: Read + Seek> FromProperty<C> for ECaveEntrancePriority {
     }
 }
 
-#[derive(Debug, Default, Serialize, FromExport, FromProperties)]
+#[derive(Debug, Clone, Default, Serialize, FromExport, FromProperties)]
 pub struct EntranceFeature {
     #[serde(flatten)]
     pub base: RoomFeatureBase,
@@ -313,7 +375,7 @@ pub struct EntranceFeature {
     pub priority: ECaveEntrancePriority,
 }
 
-#[derive(Debug, Default, Serialize, FromProperty, FromProperties)]
+#[derive(Debug, Clone, Default, Serialize, FromProperty, FromProperties)]
 pub struct FRoomLinePoint {
     pub location: FVector,
     pub h_range: f32,
@@ -327,13 +389,13 @@ pub struct FRoomLinePoint {
     pub floor_angle: f32,
 }
 
-#[derive(Debug, Default, Serialize, FromProperty, FromProperties)]
+#[derive(Debug, Clone, Default, Serialize, FromProperty, FromProperties)]
 pub struct FLayeredNoise {
-    pub noise: (), // UFloodFillSettings,
+    pub noise: Option<UFloodFillSettings>,
     pub scale: f32,
 }
 
-#[derive(Debug, Default, Serialize, FromExport, FromProperties)]
+#[derive(Debug, Clone, Default, Serialize, FromExport, FromProperties)]
 pub struct UFloodFillSettings {
     pub noise_size: FVector,
     pub freq_multiplier: f32,
@@ -346,18 +408,18 @@ pub struct UFloodFillSettings {
     pub noise_layers: Vec<FLayeredNoise>,
 }
 
-#[derive(Debug, Default, Serialize, FromExport, FromProperties)]
+#[derive(Debug, Clone, Default, Serialize, FromExport, FromProperties)]
 pub struct FloodFillLine {
     #[serde(flatten)]
     pub base: RoomFeatureBase,
-    pub wall_noise_override: (),    // Option<UFloodFillSettings>,
-    pub ceiling_noise_override: (), // Option<UFloodFillSettings>,
-    pub flood_noise_override: (),   // Option<UFloodFillSettings>,
+    pub wall_noise_override: Option<UFloodFillSettings>,
+    pub ceiling_noise_override: Option<UFloodFillSettings>,
+    pub flood_noise_override: Option<UFloodFillSettings>,
     pub use_detail_noise: bool,
     pub points: Vec<FRoomLinePoint>,
 }
 
-#[derive(Debug, Default, Serialize, FromExport, FromProperties)]
+#[derive(Debug, Clone, Default, Serialize, FromExport, FromProperties)]
 pub struct ResourceFeature {
     #[serde(flatten)]
     pub base: RoomFeatureBase,
@@ -366,7 +428,7 @@ pub struct ResourceFeature {
     pub base_amount: f32,
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub enum EItemAdjustmentType {
     #[default]
     None,
@@ -391,7 +453,7 @@ impl<C: Read + Seek> FromProperty<C> for EItemAdjustmentType {
     }
 }
 
-#[derive(Debug, Default, Serialize, FromExport, FromProperties)]
+#[derive(Debug, Clone, Default, Serialize, FromExport, FromProperties)]
 pub struct SpawnActorFeature {
     #[serde(flatten)]
     pub base: RoomFeatureBase,
@@ -404,7 +466,7 @@ pub struct SpawnActorFeature {
     pub rotation_delta: FRotator,
 }
 
-#[derive(Debug, Default, Serialize, FromExport, FromProperties)]
+#[derive(Debug, Clone, Default, Serialize, FromExport, FromProperties)]
 pub struct DropPodCalldownLocationFeature {
     #[serde(flatten)]
     pub base: RoomFeatureBase,
@@ -418,7 +480,7 @@ impl<C: Read + Seek> FromProperty<C> for RoomFeature {
     }
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub enum ERoomMirroringSupport {
     #[default]
     NotAllowed,
@@ -443,7 +505,7 @@ impl<C: Read + Seek> FromProperty<C> for ERoomMirroringSupport {
     }
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct FGameplayTagContainer {
     pub tags: Vec<String>,
 }
@@ -465,7 +527,7 @@ impl<C: Read + Seek> FromProperty<C> for FGameplayTagContainer {
     }
 }
 
-#[derive(Debug, Default, Serialize, FromExport, FromProperties)]
+#[derive(Debug, Clone, Default, Serialize, FromExport, FromProperties)]
 pub struct RoomGeneratorBase {
     pub bounds: f32,
     pub can_only_be_used_once: bool,
@@ -473,9 +535,28 @@ pub struct RoomGeneratorBase {
     pub room_tags: FGameplayTagContainer,
 }
 
-#[derive(Debug, Default, Serialize, FromExport, FromProperties)]
+#[derive(Debug, Clone, Default, Serialize, FromExport, FromProperties)]
 pub struct RoomGenerator {
     #[serde(flatten)]
     pub base: RoomGeneratorBase,
     pub room_features: Vec<RoomFeature>,
 }
+
+impl RoomGenerator {
+    /// Feature at `path`, if there is one.
+    pub fn feature(&self, path: &[usize]) -> Option<&RoomFeature> {
+        let (first, rest) = path.split_first()?;
+        rest.iter()
+            .try_fold(self.room_features.get(*first)?, |feature, &i| {
+                feature.children().get(i)
+            })
+    }
+    pub fn feature_mut(&mut self, path: &[usize]) -> Option<&mut RoomFeature> {
+        let (first, rest) = path.split_first()?;
+        let mut feature = self.room_features.get_mut(*first)?;
+        for &i in rest {
+            feature = feature.base_mut()?.room_features.get_mut(i)?;
+        }
+        Some(feature)
+    }
+}
@@ -1,13 +1,20 @@
 use three_d::{
-    BoundingBox, CpuMaterial, CpuMesh, Gm, InstancedMesh, Instances, Mesh, Object, PhysicalMaterial,
+    CpuMaterial, CpuMesh, Cull, Gm, Indices, InstancedMesh, Instances, Mesh, Object,
+    PhysicalMaterial, Positions,
 };
-use three_d_asset::{vec2, vec3, Angle, InnerSpace, Mat4, Quat, Radians, Srgba, Vector3};
+use three_d_asset::{vec3, Angle, InnerSpace, Mat4, Quat, Radians, SquareMatrix, Srgba, Vector3};
 
 use crate::{
+    bounds::BoundsReport,
+    mesh::SurfaceMesh,
     rma::{
-        DropPodCalldownLocationFeature, ECaveEntranceType, EntranceFeature, FVector, FloodFillBox,
-        FloodFillLine, FloodFillPillar, SpawnActorFeature,
+        DropPodCalldownLocationFeature, ECaveEntranceType, EItemAdjustmentType, EntranceFeature,
+        FQuat, FRotator, FVector, FloodFillBox, FloodFillLine, FloodFillPillar, RandomSelector,
+        ResourceFeature, RoomGeneratorBase, SpawnActorFeature, SpawnTriggerFeature,
     },
+    snap::{Snap, SnapOutcome},
+    walk::{Connectivity, Reach},
+    wireframe::{dash, Segment, Wireframe, TRIGGER_EXTENT},
     RMAContext,
 };
 
@@ -21,6 +28,62 @@ impl From<FVector> for Vector3<f32> {
     }
 }
 
+/// Same rotation as UE's `FRotator::Quaternion`: roll about X, then pitch about Y, then yaw about
+/// Z, with pitch and roll flipped to account for UE's left handed coordinates.
+impl From<FRotator> for Quat {
+    fn from(val: FRotator) -> Self {
+        let half = |deg: f32| (deg.to_radians() / 2.0).sin_cos();
+        let (sy, cy) = half(val.yaw);
+        let (sp, cp) = half(-val.pitch);
+        let (sr, cr) = half(-val.roll);
+        Quat::new(cy, 0.0, 0.0, sy) * Quat::new(cp, 0.0, sp, 0.0) * Quat::new(cr, sr, 0.0, 0.0)
+    }
+}
+
+/// Inverse of the `FRotator` to `Quat` conversion, read from the rotated axes like UE's
+/// `FMatrix::Rotator`.
+impl From<Quat> for FRotator {
+    fn from(val: Quat) -> Self {
+        let x = val * vec3(1.0, 0.0, 0.0);
+        let y = val * vec3(0.0, 1.0, 0.0);
+        let z = val * vec3(0.0, 0.0, 1.0);
+        FRotator {
+            pitch: x.z.clamp(-1.0, 1.0).asin().to_degrees(),
+            yaw: x.y.atan2(x.x).to_degrees(),
+            roll: (-y.z).atan2(z.z).to_degrees(),
+        }
+    }
+}
+
+/// `FQuat` and `Quat` share UE's coordinates, so only the order of the components differs.
+impl From<FQuat> for Quat {
+    fn from(val: FQuat) -> Self {
+        Quat::new(val.w, val.x, val.y, val.z)
+    }
+}
+
+impl From<Quat> for FQuat {
+    fn from(val: Quat) -> Self {
+        FQuat {
+            x: val.v.x,
+            y: val.v.y,
+            z: val.v.z,
+            w: val.s,
+        }
+    }
+}
+
+impl From<&SurfaceMesh> for CpuMesh {
+    fn from(val: &SurfaceMesh) -> Self {
+        CpuMesh {
+            positions: Positions::F32(val.positions.clone()),
+            indices: Indices::U32(val.indices.clone()),
+            normals: Some(val.normals.clone()),
+            ..Default::default()
+        }
+    }
+}
+
 pub fn line_transform(p1: Vector3<f32>, p2: Vector3<f32>) -> Mat4 {
     Mat4::from_translation(p1)
         * Into::<Mat4>::into(Quat::from_arc(
@@ -31,171 +94,233 @@ pub fn line_transform(p1: Vector3<f32>, p2: Vector3<f32>) -> Mat4 {
         * Mat4::from_nonuniform_scale((p1 - p2).magnitude(), 1.0, 1.0)
 }
 
+/// Instanced lines along `segments`.
+fn build_wireframe(ctx: &RMAContext, segments: Vec<Segment>) -> Box<dyn Object> {
+    build_wireframe_with(ctx, segments, ctx.wireframe_material.clone())
+}
+
+/// Instanced lines along `segments` drawn with `material`.
+fn build_wireframe_with(
+    ctx: &RMAContext,
+    segments: Vec<Segment>,
+    material: PhysicalMaterial,
+) -> Box<dyn Object> {
+    let transformations = segments
+        .into_iter()
+        .map(|[p1, p2]| line_transform(p1, p2))
+        .collect();
+    Box::new(Gm::new(
+        InstancedMesh::new(
+            ctx.context,
+            &Instances {
+                transformations,
+                ..Default::default()
+            },
+            &ctx.wireframe_mesh,
+        ),
+        material,
+    ))
+}
+
+/// Faint instanced lines for the extent of noise, if there is any.
+fn build_noise_wireframe(ctx: &RMAContext, segments: Vec<Segment>) -> Option<Box<dyn Object>> {
+    if segments.is_empty() {
+        return None;
+    }
+    Some(build_wireframe_with(
+        ctx,
+        segments,
+        ctx.noise_material.clone(),
+    ))
+}
+
+/// Semi-transparent surface, visible from both sides.
+fn build_surface(ctx: &RMAContext, mesh: &SurfaceMesh, albedo: Srgba) -> Box<dyn Object> {
+    let mut material = PhysicalMaterial::new_transparent(
+        ctx.context,
+        &CpuMaterial {
+            albedo,
+            ..Default::default()
+        },
+    );
+    material.render_states.cull = Cull::None;
+    Box::new(Gm::new(Mesh::new(ctx.context, &mesh.into()), material))
+}
+
 impl RoomFeatureTrait for FloodFillBox {
     fn build(&self, ctx: &RMAContext) -> Vec<Box<dyn Object>> {
-        // only used in RMA_Escort10
-        let mut mesh = BoundingBox::new(ctx.context, CpuMesh::cube().compute_aabb());
-        mesh.set_transformation(
-            Mat4::from_translation(self.position.into())
-                * Mat4::from_nonuniform_scale(self.extends.x, self.extends.y, self.extends.z),
-        );
-
-        vec![Box::new(Gm::new(mesh, ctx.wireframe_material.clone()))]
+        let mut objects = vec![if self.is_carver {
+            build_wireframe(ctx, self.wireframe())
+        } else {
+            // filling boxes put rock back, so they are drawn in the color of rock
+            let material = PhysicalMaterial::new_opaque(
+                ctx.context,
+                &CpuMaterial {
+                    albedo: Srgba {
+                        r: 170,
+                        g: 160,
+                        b: 150,
+                        a: 255,
+                    },
+                    ..Default::default()
+                },
+            );
+            build_wireframe_with(ctx, self.wireframe(), material)
+        }];
+        objects.extend(build_noise_wireframe(ctx, self.noise_wireframe()));
+        objects
     }
 }
 
 impl RoomFeatureTrait for FloodFillPillar {
     fn build(&self, ctx: &RMAContext) -> Vec<Box<dyn Object>> {
-        let mut transformations = Vec::new();
-
-        let mut add_line = |p1, p2| transformations.push(line_transform(p1, p2));
-
-        for pair in self.points.windows(2) {
-            add_line(pair[0].location.into(), pair[1].location.into());
+        if !ctx.surfaces {
+            let mut objects = vec![build_wireframe(ctx, self.wireframe())];
+            objects.extend(build_noise_wireframe(ctx, self.noise_wireframe()));
+            return objects;
         }
-
-        vec![Box::new(Gm::new(
-            InstancedMesh::new(
-                ctx.context,
-                &Instances {
-                    transformations,
-                    ..Default::default()
+        let [min, max] = self.tubes();
+        vec![
+            build_surface(
+                ctx,
+                &min,
+                Srgba {
+                    r: 90,
+                    g: 80,
+                    b: 75,
+                    a: 200,
+                },
+            ),
+            build_surface(
+                ctx,
+                &max,
+                Srgba {
+                    r: 160,
+                    g: 150,
+                    b: 140,
+                    a: 60,
                 },
-                &ctx.wireframe_mesh,
             ),
-            ctx.wireframe_material.clone(),
-        ))]
+        ]
+    }
+}
+
+impl EItemAdjustmentType {
+    /// Color of the adjustment ray, by the surface it snaps to.
+    pub fn color(&self) -> Srgba {
+        match self {
+            EItemAdjustmentType::None => Srgba {
+                r: 255,
+                g: 200,
+                b: 0,
+                a: 255,
+            },
+            EItemAdjustmentType::Cieling => Srgba {
+                r: 120,
+                g: 160,
+                b: 255,
+                a: 255,
+            },
+            EItemAdjustmentType::Wall => Srgba {
+                r: 255,
+                g: 120,
+                b: 200,
+                a: 255,
+            },
+            EItemAdjustmentType::Floor => Srgba {
+                r: 140,
+                g: 220,
+                b: 80,
+                a: 255,
+            },
+        }
     }
 }
 
 impl RoomFeatureTrait for SpawnActorFeature {
     fn build(&self, ctx: &RMAContext) -> Vec<Box<dyn Object>> {
+        let albedo = Srgba {
+            r: 255,
+            g: 200,
+            b: 0,
+            a: 200,
+        };
+        let cone = |scale: Vector3<f32>| {
+            Mat4::from_translation(self.location.into())
+                * Mat4::from_nonuniform_scale(100.0 * scale.x, 100.0 * scale.y, 300.0 * scale.z)
+                * Mat4::from_angle_y(-Radians::turn_div_4())
+        };
         let mut obj = Gm::new(
             Mesh::new(ctx.context, &CpuMesh::cone(16)),
             PhysicalMaterial::new_opaque(
                 ctx.context,
                 &CpuMaterial {
-                    albedo: Srgba {
-                        r: 255,
-                        g: 200,
-                        b: 0,
-                        a: 200,
-                    },
+                    albedo,
                     ..Default::default()
                 },
             ),
         );
-        obj.set_transformation(
-            Mat4::from_translation(self.location.into())
-                * Mat4::from_nonuniform_scale(100.0, 100.0, 300.0)
-                * Mat4::from_angle_y(-Radians::turn_div_4()),
-        );
-        vec![Box::new(obj)]
-    }
-}
-
-impl RoomFeatureTrait for FloodFillLine {
-    fn build(&self, ctx: &RMAContext) -> Vec<Box<dyn Object>> {
-        let mut transformations = Vec::new();
-
-        let mut add_line = |p1, p2| transformations.push(line_transform(p1, p2));
-
-        for pair in self.points.windows(2) {
-            let (p1, p2) = (&pair[0], &pair[1]);
-            let v1: Vector3<f32> = p1.location.into();
-            let v2: Vector3<f32> = p2.location.into();
-            //add_line(v1, v2);
-
-            let d = v1.truncate() - v2.truncate();
-            let d = d / d.magnitude();
-            let d = vec2(-d.y, d.x);
-
-            let o1 = (p1.h_range * d).extend(0.);
-            let o2 = (p2.h_range * d).extend(0.);
-            add_line(v1 + o1, v2 + o2);
-            add_line(v1 - o1, v2 - o2);
-            add_line(v1 + vec3(0., 0., p1.v_range), v2 + vec3(0., 0., p2.v_range));
-        }
-
-        // horizontal perimeter circle
-        for point in &self.points {
-            let segments = 40;
-            let mut iter = (0..segments + 1)
-                .map(|i| {
-                    let angle = 2.0 * std::f32::consts::PI * i as f32 / segments as f32;
-                    (angle.cos(), angle.sin())
-                })
-                .peekable();
-            while let (Some(a), Some(b)) = (iter.next(), iter.peek()) {
-                add_line(
-                    vec3(
-                        point.location.x + point.h_range * a.0,
-                        point.location.y + point.h_range * a.1,
-                        point.location.z,
-                    ),
-                    vec3(
-                        point.location.x + point.h_range * b.0,
-                        point.location.y + point.h_range * b.1,
-                        point.location.z,
-                    ),
-                );
-            }
-        }
+        obj.set_transformation(cone(vec3(1.0, 1.0, 1.0)));
+        let mut objects: Vec<Box<dyn Object>> = vec![Box::new(obj)];
 
-        // vertical half circles
-        for point in &self.points {
-            let segments = 40;
-            let mut iter = (0..segments / 2 + 1)
-                .map(|i| {
-                    let angle = 2.0 * std::f32::consts::PI * i as f32 / segments as f32;
-                    (angle.cos(), angle.sin())
-                })
-                .peekable();
-            while let (Some(a), Some(b)) = (iter.next(), iter.peek()) {
-                add_line(
-                    vec3(
-                        point.location.x + point.h_range * a.0,
-                        point.location.y,
-                        point.location.z + point.v_range * a.1,
-                    ),
-                    vec3(
-                        point.location.x + point.h_range * b.0,
-                        point.location.y,
-                        point.location.z + point.v_range * b.1,
-                    ),
-                );
-                add_line(
-                    vec3(
-                        point.location.x,
-                        point.location.y + point.h_range * a.0,
-                        point.location.z + point.v_range * a.1,
-                    ),
-                    vec3(
-                        point.location.x,
-                        point.location.y + point.h_range * b.0,
-                        point.location.z + point.v_range * b.1,
-                    ),
+        // ghosts of the smallest and largest actor, unless it is never scaled
+        let (min, max) = self.scale_range();
+        if min != vec3(1.0, 1.0, 1.0) || max != vec3(1.0, 1.0, 1.0) {
+            for (scale, a) in [(min, 110), (max, 40)] {
+                let mut material = PhysicalMaterial::new_transparent(
+                    ctx.context,
+                    &CpuMaterial {
+                        albedo: Srgba { a, ..albedo },
+                        ..Default::default()
+                    },
                 );
+                material.render_states.cull = Cull::None;
+                let mut ghost = Gm::new(Mesh::new(ctx.context, &CpuMesh::cone(16)), material);
+                ghost.set_transformation(cone(scale));
+                objects.push(Box::new(ghost));
             }
         }
 
-        vec![Box::new(Gm::new(
-            InstancedMesh::new(
+        let segments = self.wireframe();
+        if !segments.is_empty() {
+            let material = PhysicalMaterial::new_opaque(
                 ctx.context,
-                &Instances {
-                    transformations,
+                &CpuMaterial {
+                    albedo: self.adjustment.color(),
                     ..Default::default()
                 },
-                &ctx.wireframe_mesh,
-            ),
-            ctx.wireframe_material.clone(),
-        ))]
+            );
+            objects.push(build_wireframe_with(ctx, segments, material));
+        }
+        objects
     }
 }
 
-impl RoomFeatureTrait for EntranceFeature {
+impl RoomFeatureTrait for FloodFillLine {
     fn build(&self, ctx: &RMAContext) -> Vec<Box<dyn Object>> {
-        let albedo = match self.entrance_type {
+        let mut objects = vec![if ctx.surfaces {
+            build_surface(
+                ctx,
+                &self.tube(),
+                Srgba {
+                    r: 255,
+                    g: 0,
+                    b: 0,
+                    a: 70,
+                },
+            )
+        } else {
+            build_wireframe(ctx, self.wireframe())
+        }];
+        objects.extend(build_noise_wireframe(ctx, self.noise_wireframe()));
+        objects
+    }
+}
+
+impl ECaveEntranceType {
+    /// Color entrances of this type are drawn with.
+    pub fn color(&self) -> Srgba {
+        match self {
             ECaveEntranceType::EntranceAndExit => Srgba {
                 r: 0,
                 g: 255,
@@ -220,48 +345,317 @@ impl RoomFeatureTrait for EntranceFeature {
                 b: 0,
                 a: 200,
             },
+        }
+    }
+}
+
+impl RoomFeatureTrait for EntranceFeature {
+    fn build(&self, ctx: &RMAContext) -> Vec<Box<dyn Object>> {
+        let albedo = self.entrance_type.color();
+        let material = PhysicalMaterial::new_opaque(
+            ctx.context,
+            &CpuMaterial {
+                albedo,
+                ..Default::default()
+            },
+        );
+        // a distinct shape per type, so types can be told apart without color
+        let (mesh, shape) = match self.entrance_type {
+            ECaveEntranceType::EntranceAndExit => (CpuMesh::sphere(16), Mat4::identity()),
+            ECaveEntranceType::Entrance => (
+                CpuMesh::cone(16),
+                Mat4::from_nonuniform_scale(2.0, 1.0, 1.0)
+                    * Mat4::from_translation(vec3(-0.5, 0.0, 0.0)),
+            ),
+            ECaveEntranceType::Exit => (CpuMesh::cube(), Mat4::from_scale(0.8)),
+            ECaveEntranceType::TreassureRoom => (
+                CpuMesh::cylinder(16),
+                Mat4::from_nonuniform_scale(2.0, 1.0, 1.0)
+                    * Mat4::from_translation(vec3(-0.5, 0.0, 0.0)),
+            ),
         };
+        let rotation: Quat = self.direction.into();
+        let mut marker = Gm::new(Mesh::new(ctx.context, &mesh), material.clone());
+        marker.set_transformation(
+            Mat4::from_translation(self.location.into())
+                * Mat4::from(rotation)
+                * Mat4::from_scale(100.0)
+                * shape,
+        );
+        vec![
+            Box::new(marker),
+            build_wireframe_with(ctx, self.wireframe(), material),
+        ]
+    }
+}
+
+impl RoomFeatureTrait for DropPodCalldownLocationFeature {
+    fn build(&self, ctx: &RMAContext) -> Vec<Box<dyn Object>> {
         let mut sphere = Gm::new(
-            Mesh::new(ctx.context, &CpuMesh::sphere(16)),
+            Mesh::new(ctx.context, &CpuMesh::cylinder(16)),
             PhysicalMaterial::new_opaque(
                 ctx.context,
                 &CpuMaterial {
-                    albedo,
+                    albedo: Srgba {
+                        r: 0,
+                        g: 255,
+                        b: 0,
+                        a: 200,
+                    },
                     ..Default::default()
                 },
             ),
         );
-        // TODO there's also a direction component but I can't be bothered to figure out how it's
-        // mapped at this moment
         sphere.set_transformation(
-            Mat4::from_translation(self.location.into()) * Mat4::from_scale(100.0),
+            Mat4::from_translation(self.location.into())
+                * Mat4::from_nonuniform_scale(100.0, 100.0, 300.0)
+                * Mat4::from_angle_y(Radians::turn_div_4()),
         );
         vec![Box::new(sphere)]
     }
 }
 
-impl RoomFeatureTrait for DropPodCalldownLocationFeature {
+impl RoomFeatureTrait for SpawnTriggerFeature {
+    fn build(&self, ctx: &RMAContext) -> Vec<Box<dyn Object>> {
+        let albedo = Srgba {
+            r: 0,
+            g: 200,
+            b: 160,
+            a: 50,
+        };
+        let mut volume = PhysicalMaterial::new_transparent(
+            ctx.context,
+            &CpuMaterial {
+                albedo,
+                ..Default::default()
+            },
+        );
+        volume.render_states.cull = Cull::None;
+        let mut cube = Gm::new(Mesh::new(ctx.context, &CpuMesh::cube()), volume);
+        let rotation: Quat = self.transform.rotation.into();
+        let extent = self.scale() * TRIGGER_EXTENT;
+        cube.set_transformation(
+            Mat4::from_translation(self.transform.translation.into())
+                * Mat4::from(rotation)
+                * Mat4::from_nonuniform_scale(extent.x, extent.y, extent.z),
+        );
+        let edges = PhysicalMaterial::new_opaque(
+            ctx.context,
+            &CpuMaterial {
+                albedo: Srgba { a: 255, ..albedo },
+                ..Default::default()
+            },
+        );
+        vec![
+            Box::new(cube),
+            build_wireframe_with(ctx, self.wireframe(), edges),
+        ]
+    }
+}
+
+impl RoomFeatureTrait for ResourceFeature {
     fn build(&self, ctx: &RMAContext) -> Vec<Box<dyn Object>> {
         let mut sphere = Gm::new(
-            Mesh::new(ctx.context, &CpuMesh::cylinder(16)),
+            Mesh::new(ctx.context, &CpuMesh::sphere(16)),
             PhysicalMaterial::new_opaque(
                 ctx.context,
                 &CpuMaterial {
                     albedo: Srgba {
-                        r: 0,
-                        g: 255,
-                        b: 0,
+                        r: 160,
+                        g: 60,
+                        b: 255,
                         a: 200,
                     },
                     ..Default::default()
                 },
             ),
         );
+        // volume proportional to the amount, so deposits can be compared at a glance
+        let radius = 25.0 * self.base_amount.max(1.0).cbrt();
         sphere.set_transformation(
-            Mat4::from_translation(self.location.into())
-                * Mat4::from_nonuniform_scale(100.0, 100.0, 300.0)
-                * Mat4::from_angle_y(Radians::turn_div_4()),
+            Mat4::from_translation(self.location.into()) * Mat4::from_scale(radius),
         );
         vec![Box::new(sphere)]
     }
 }
+
+impl RoomFeatureTrait for RandomSelector {
+    /// Only the group outline, the children are built on their own.
+    fn build(&self, ctx: &RMAContext) -> Vec<Box<dyn Object>> {
+        let segments = self.group_wireframe();
+        if segments.is_empty() {
+            return vec![];
+        }
+        let material = PhysicalMaterial::new_opaque(
+            ctx.context,
+            &CpuMaterial {
+                albedo: Srgba {
+                    r: 255,
+                    g: 0,
+                    b: 255,
+                    a: 255,
+                },
+                ..Default::default()
+            },
+        );
+        vec![build_wireframe_with(ctx, segments, material)]
+    }
+}
+
+/// Colour of overlay markers flagging a problem.
+const PROBLEM_RED: Srgba = Srgba {
+    r: 255,
+    g: 40,
+    b: 40,
+    a: 255,
+};
+
+fn opaque_material(ctx: &RMAContext, albedo: Srgba) -> PhysicalMaterial {
+    PhysicalMaterial::new_opaque(
+        ctx.context,
+        &CpuMaterial {
+            albedo,
+            ..Default::default()
+        },
+    )
+}
+
+/// Sphere of the given radius marking a point in an overlay.
+fn marker(ctx: &RMAContext, position: Vector3<f32>, radius: f32, albedo: Srgba) -> Box<dyn Object> {
+    let mut sphere = Gm::new(
+        Mesh::new(ctx.context, &CpuMesh::sphere(12)),
+        opaque_material(ctx, albedo),
+    );
+    sphere.set_transformation(Mat4::from_translation(position) * Mat4::from_scale(radius));
+    Box::new(sphere)
+}
+
+/// Overlay of simulated spawn snapping: the path to the surface for spawns that reach it, red
+/// rays for spawns that miss and red spheres for spawns starting in rock.
+pub fn build_snaps(ctx: &RMAContext, snaps: &[Snap], max_distance: f32) -> Vec<Box<dyn Object>> {
+    let mut objects = vec![];
+    let (mut paths, mut misses) = (vec![], vec![]);
+    for snap in snaps {
+        match snap.outcome {
+            SnapOutcome::Hit { position, .. } => {
+                paths.push([snap.start, position]);
+                objects.push(marker(ctx, position, 30.0, snap.adjustment.color()));
+            }
+            SnapOutcome::Missed => {
+                misses.push([snap.start, snap.start + snap.direction * max_distance]);
+            }
+            SnapOutcome::InRock => objects.push(marker(ctx, snap.start, 60.0, PROBLEM_RED)),
+        }
+    }
+    if !paths.is_empty() {
+        objects.push(build_wireframe_with(
+            ctx,
+            paths,
+            opaque_material(ctx, Srgba::WHITE),
+        ));
+    }
+    if !misses.is_empty() {
+        objects.push(build_wireframe_with(
+            ctx,
+            dash(misses, 40),
+            opaque_material(ctx, PROBLEM_RED),
+        ));
+    }
+    objects
+}
+
+/// Overlay of the declared bounds sphere, a dashed box around every feature and a red box
+/// around each feature reaching outside the bounds.
+pub fn build_bounds(
+    ctx: &RMAContext,
+    base: &RoomGeneratorBase,
+    report: &BoundsReport,
+) -> Vec<Box<dyn Object>> {
+    let mut objects = vec![build_wireframe_with(
+        ctx,
+        base.wireframe(),
+        opaque_material(
+            ctx,
+            Srgba {
+                r: 80,
+                g: 160,
+                b: 255,
+                a: 255,
+            },
+        ),
+    )];
+    if let Some(extent) = report.extent {
+        objects.push(build_wireframe_with(
+            ctx,
+            dash(extent.wireframe(), 16),
+            opaque_material(
+                ctx,
+                Srgba {
+                    r: 180,
+                    g: 180,
+                    b: 180,
+                    a: 255,
+                },
+            ),
+        ));
+    }
+    let outside = report
+        .outside
+        .iter()
+        .flat_map(|o| o.extent.wireframe())
+        .collect::<Vec<_>>();
+    if !outside.is_empty() {
+        objects.push(build_wireframe_with(
+            ctx,
+            outside,
+            opaque_material(ctx, PROBLEM_RED),
+        ));
+    }
+    objects
+}
+
+/// Overlay of the walk from the root to every reachable entrance and drop pod location, with red
+/// markers on the ones cut off from it and orange markers on the ones without a floor.
+pub fn build_walk(ctx: &RMAContext, connectivity: &Connectivity) -> Vec<Box<dyn Object>> {
+    let green = Srgba {
+        r: 60,
+        g: 230,
+        b: 90,
+        a: 255,
+    };
+
+    let mut objects = vec![];
+    let mut paths = vec![];
+    for (i, route) in connectivity.routes.iter().enumerate() {
+        match &route.reach {
+            Reach::Connected { points, .. } => {
+                // lifted off the floor so it is not hidden by the cave
+                let lift = vec3(0.0, 0.0, 20.0);
+                paths.extend(points.windows(2).map(|w| [w[0] + lift, w[1] + lift]));
+                if connectivity.root == Some(i) {
+                    objects.push(marker(ctx, route.location, 50.0, green));
+                }
+            }
+            Reach::Disconnected => objects.push(marker(ctx, route.location, 60.0, PROBLEM_RED)),
+            Reach::NoFloor => objects.push(marker(
+                ctx,
+                route.location,
+                60.0,
+                Srgba {
+                    r: 255,
+                    g: 150,
+                    b: 30,
+                    a: 255,
+                },
+            )),
+        }
+    }
+    if !paths.is_empty() {
+        objects.push(build_wireframe_with(
+            ctx,
+            paths,
+            opaque_material(ctx, green),
+        ));
+    }
+    objects
+}
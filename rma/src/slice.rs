@@ -0,0 +1,265 @@
+//! Planar cross-sections of the carved volume, for checking floor and ceiling clearance.
+//!
+//! Slices sample the [`DensityGrid`] on a plane and mark where feature outlines cross it, so the
+//! authored ranges can be compared against what is actually carved.
+
+use std::ops::Range;
+
+use three_d_asset::{vec2, vec3, InnerSpace, Vector2, Vector3};
+
+use crate::{
+    raster::Image,
+    rma::{iter_features, RoomFeature, RoomGenerator},
+    voxel::DensityGrid,
+    wireframe::Wireframe,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlicePlane {
+    /// Horizontal plane at height `z`, seen from above.
+    Horizontal { z: f32 },
+    /// Vertical plane through `origin` running along `yaw` (in degrees), drawn with that
+    /// direction to the right and Z up.
+    Vertical { origin: Vector2<f32>, yaw: f32 },
+}
+
+impl SlicePlane {
+    /// Point on the plane.
+    fn point(self) -> Vector3<f32> {
+        match self {
+            SlicePlane::Horizontal { z } => vec3(0.0, 0.0, z),
+            SlicePlane::Vertical { origin, .. } => origin.extend(0.0),
+        }
+    }
+
+    fn normal(self) -> Vector3<f32> {
+        match self {
+            SlicePlane::Horizontal { .. } => vec3(0.0, 0.0, 1.0),
+            SlicePlane::Vertical { yaw, .. } => {
+                let (sin, cos) = yaw.to_radians().sin_cos();
+                vec3(-sin, cos, 0.0)
+            }
+        }
+    }
+
+    /// Unit axes of the image, with the second pointing down the image. Looking down with X to
+    /// the right puts Y downwards since UE is left handed.
+    fn axes(self) -> (Vector3<f32>, Vector3<f32>) {
+        match self {
+            SlicePlane::Horizontal { .. } => (vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)),
+            SlicePlane::Vertical { yaw, .. } => {
+                let (sin, cos) = yaw.to_radians().sin_cos();
+                (vec3(cos, sin, 0.0), vec3(0.0, 0.0, -1.0))
+            }
+        }
+    }
+
+    /// Signed distance of `p` from the plane.
+    pub fn distance(self, p: Vector3<f32>) -> f32 {
+        (p - self.point()).dot(self.normal())
+    }
+}
+
+/// Density values sampled on a plane, covering the part of the grid it intersects.
+#[derive(Debug, Clone)]
+pub struct Slice {
+    pub plane: SlicePlane,
+    /// World position of the top left corner of the image.
+    pub origin: Vector3<f32>,
+    /// Room units per pixel.
+    pub resolution: f32,
+    pub width: u32,
+    pub height: u32,
+    /// Sampled density, row by row from the top. Negative values are open space.
+    pub values: Vec<f32>,
+}
+
+const ROCK_COLOR: [f32; 3] = [0.16, 0.14, 0.13];
+const WALL_COLOR: [f32; 3] = [0.5, 0.4, 0.33];
+const OPEN_COLOR: [f32; 3] = [0.95, 0.9, 0.82];
+const SURFACE_COLOR: [f32; 3] = [1.0, 1.0, 1.0];
+const CROSSING_COLOR: [f32; 3] = [1.0, 0.0, 0.0];
+/// Distance from the surface at which open space is drawn in [`OPEN_COLOR`].
+const OPEN_FADE: f32 = 500.0;
+/// Spacing of the reference grid, 5 meters.
+const GRID_SPACING: f32 = 500.0;
+
+impl Slice {
+    pub fn new(grid: &DensityGrid, plane: SlicePlane, resolution: f32) -> Self {
+        debug_assert!(resolution > 0.0, "resolution must be positive");
+        let (u, v) = plane.axes();
+        let lo = grid.origin;
+        let hi = grid.position(
+            grid.dims[0].saturating_sub(1),
+            grid.dims[1].saturating_sub(1),
+            grid.dims[2].saturating_sub(1),
+        );
+        // extent of the grid's corners along both image axes
+        let corners = (0..8).map(|i| {
+            vec3(
+                if i & 1 == 0 { lo.x } else { hi.x },
+                if i & 2 == 0 { lo.y } else { hi.y },
+                if i & 4 == 0 { lo.z } else { hi.z },
+            )
+        });
+        let (mut min, mut max) = (vec2(f32::MAX, f32::MAX), vec2(f32::MIN, f32::MIN));
+        for c in corners {
+            let p = vec2((c - plane.point()).dot(u), (c - plane.point()).dot(v));
+            min = vec2(min.x.min(p.x), min.y.min(p.y));
+            max = vec2(max.x.max(p.x), max.y.max(p.y));
+        }
+
+        let origin = plane.point() + u * min.x + v * min.y;
+        let width = ((max.x - min.x) / resolution).ceil().max(1.0) as u32;
+        let height = ((max.y - min.y) / resolution).ceil().max(1.0) as u32;
+        let mut slice = Self {
+            plane,
+            origin,
+            resolution,
+            width,
+            height,
+            values: Vec::with_capacity((width * height) as usize),
+        };
+        for y in 0..height {
+            for x in 0..width {
+                let p = slice.position(vec2(x as f32 + 0.5, y as f32 + 0.5));
+                slice.values.push(grid.sample(p));
+            }
+        }
+        slice
+    }
+
+    /// World position of a point in the image, in pixels.
+    pub fn position(&self, pixel: Vector2<f32>) -> Vector3<f32> {
+        let (u, v) = self.plane.axes();
+        self.origin + (u * pixel.x + v * pixel.y) * self.resolution
+    }
+
+    /// Position in the image of `p` projected onto the plane, in pixels.
+    pub fn pixel(&self, p: Vector3<f32>) -> Vector2<f32> {
+        let (u, v) = self.plane.axes();
+        let d = p - self.origin;
+        vec2(d.dot(u), d.dot(v)) / self.resolution
+    }
+
+    fn value(&self, x: u32, y: u32) -> f32 {
+        self.values[(y * self.width + x) as usize]
+    }
+
+    /// Rock in dark, open space fading in with distance from the surface, the surface itself,
+    /// a 5 meter reference grid and a cross at every crossing.
+    pub fn image(&self, crossings: &[Vector3<f32>]) -> Image {
+        let mut image = Image::new(self.width, self.height);
+        let grid_origin = self.pixel(vec3(0.0, 0.0, 0.0));
+        let grid_spacing = GRID_SPACING / self.resolution;
+        let on_grid = |pixel: f32, origin: f32| {
+            grid_spacing >= 4.0 && (pixel - origin).rem_euclid(grid_spacing) < 1.0
+        };
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let value = self.value(x, y);
+                let open = value < 0.0;
+                let surface = [(x + 1, y), (x, y + 1)].into_iter().any(|(nx, ny)| {
+                    nx < self.width && ny < self.height && (self.value(nx, ny) < 0.0) != open
+                });
+                let mut color = if surface {
+                    SURFACE_COLOR
+                } else if open {
+                    let t = (-value / OPEN_FADE).min(1.0);
+                    [0, 1, 2].map(|c| WALL_COLOR[c] + (OPEN_COLOR[c] - WALL_COLOR[c]) * t)
+                } else {
+                    ROCK_COLOR
+                };
+                if on_grid(x as f32, grid_origin.x) || on_grid(y as f32, grid_origin.y) {
+                    color = color.map(|c| c * 0.85 + 0.1);
+                }
+                image.set(x as i32, y as i32, color);
+            }
+        }
+        for &p in crossings {
+            let c = self.pixel(p);
+            let (x, y) = (c.x.floor() as i32, c.y.floor() as i32);
+            for d in -3..=3 {
+                image.set(x + d, y + d, CROSSING_COLOR);
+                image.set(x + d, y - d, CROSSING_COLOR);
+            }
+        }
+        image
+    }
+}
+
+/// Points where the outlines of flood fill features cross `plane`.
+pub fn crossings(room: &RoomGenerator, plane: SlicePlane) -> Vec<Vector3<f32>> {
+    let mut points = vec![];
+    iter_features(&room.room_features, &mut vec![], &mut |feature, _| {
+        let segments = match feature {
+            RoomFeature::FloodFillLine(f) => f.wireframe(),
+            RoomFeature::FloodFillPillar(f) => f.wireframe(),
+            RoomFeature::FloodFillBox(f) => f.wireframe(),
+            _ => vec![],
+        };
+        for [a, b] in segments {
+            let (da, db) = (plane.distance(a), plane.distance(b));
+            // half open so points shared by consecutive segments are only counted once
+            if (da <= 0.0) != (db <= 0.0) {
+                points.push(a + (b - a) * (da / (da - db)));
+            }
+        }
+    });
+    points
+}
+
+impl DensityGrid {
+    /// Open intervals of the vertical column at `x`, `y`, from floor to ceiling.
+    pub fn clearance(&self, x: f32, y: f32) -> Vec<Range<f32>> {
+        let mut spans = vec![];
+        let mut start = None;
+        let mut previous: Option<(f32, f32)> = None;
+        for k in 0..self.dims[2] {
+            let z = self.origin.z + k as f32 * self.voxel_size;
+            let value = self.sample(vec3(x, y, z));
+            if let Some((pz, pv)) = previous {
+                if (pv < 0.0) != (value < 0.0) {
+                    let crossing = pz + (z - pz) * (pv / (pv - value));
+                    match start.take() {
+                        Some(floor) => spans.push(floor..crossing),
+                        None => start = Some(crossing),
+                    }
+                }
+            }
+            previous = Some((z, value));
+        }
+        spans
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::voxel::{tunnel, CarveSettings};
+
+    use super::*;
+
+    #[test]
+    fn test_slice_line() {
+        let room = RoomGenerator {
+            room_features: vec![tunnel(0.0, 0.0, 2000.0)],
+            ..Default::default()
+        };
+        let grid = DensityGrid::carve(&room, &CarveSettings::default());
+
+        let clearance = grid.clearance(1000.0, 0.0);
+        assert_eq!(clearance.len(), 1);
+        assert!((clearance[0].start + 100.0).abs() < 10.0);
+        assert!((clearance[0].end - 400.0).abs() < 10.0);
+
+        let plane = SlicePlane::Vertical {
+            origin: vec2(0.0, 0.0),
+            yaw: 0.0,
+        };
+        let slice = Slice::new(&grid, plane, 20.0);
+        let inside = slice.pixel(vec3(1000.0, 0.0, 100.0));
+        let value = slice.values[(inside.y as u32 * slice.width + inside.x as u32) as usize];
+        assert!(value < 0.0);
+        assert!(crossings(&room, plane).len() >= 2);
+    }
+}
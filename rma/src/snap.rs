@@ -0,0 +1,155 @@
+//! Where spawned actors end up after being adjusted onto terrain, simulated against the carved
+//! volume.
+//!
+//! A spawn with an [`EItemAdjustmentType`] other than `None` is moved along its
+//! `adjustment_direction` until it meets rock. The game traces against the generated mesh, so
+//! this is only as accurate as the [`DensityGrid`] it marches through.
+
+use three_d_asset::Vector3;
+
+use crate::{
+    rma::{iter_features, EItemAdjustmentType, FeaturePath, RoomFeature, RoomGenerator},
+    voxel::DensityGrid,
+};
+
+/// How far a spawn is traced before it is considered to have missed.
+pub const MAX_SNAP_DISTANCE: f32 = 5000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapOutcome {
+    /// Reached rock at `position`, `distance` units from where it spawned.
+    Hit {
+        position: Vector3<f32>,
+        distance: f32,
+    },
+    /// Still in open space after the maximum distance.
+    Missed,
+    /// Spawned inside rock, so it has nothing to move towards.
+    InRock,
+}
+
+#[derive(Debug, Clone)]
+pub struct Snap {
+    pub path: FeaturePath,
+    pub start: Vector3<f32>,
+    /// Unit direction the spawn is moved in.
+    pub direction: Vector3<f32>,
+    pub adjustment: EItemAdjustmentType,
+    pub outcome: SnapOutcome,
+}
+
+impl DensityGrid {
+    /// Distance from `start` along the unit `direction` to the first rock, if it is within
+    /// `max_distance`. The crossing is interpolated between samples half a voxel apart.
+    pub fn raycast(
+        &self,
+        start: Vector3<f32>,
+        direction: Vector3<f32>,
+        max_distance: f32,
+    ) -> Option<f32> {
+        let step = self.voxel_size / 2.0;
+        let mut previous = (0.0, self.sample(start));
+        if previous.1 >= 0.0 {
+            return Some(0.0);
+        }
+        let mut t = 0.0;
+        while t < max_distance {
+            t = (t + step).min(max_distance);
+            let value = self.sample(start + direction * t);
+            if value >= 0.0 {
+                let (pt, pv) = previous;
+                return Some(pt + (t - pt) * (pv / (pv - value)));
+            }
+            previous = (t, value);
+        }
+        None
+    }
+}
+
+/// Snap every adjusted spawn in `room`, parents first. Pass an instantiated room and the grid
+/// carved from it to simulate a single variant.
+pub fn simulate(room: &RoomGenerator, grid: &DensityGrid, max_distance: f32) -> Vec<Snap> {
+    let mut snaps = vec![];
+    iter_features(&room.room_features, &mut vec![], &mut |feature, path| {
+        let RoomFeature::SpawnActorFeature(spawn) = feature else {
+            return;
+        };
+        let Some(direction) = spawn.adjustment_ray() else {
+            return;
+        };
+        let start = Vector3::from(spawn.location);
+        let outcome = if !grid.is_open(start) {
+            SnapOutcome::InRock
+        } else {
+            match grid.raycast(start, direction, max_distance) {
+                Some(distance) => SnapOutcome::Hit {
+                    position: start + direction * distance,
+                    distance,
+                },
+                None => SnapOutcome::Missed,
+            }
+        };
+        snaps.push(Snap {
+            path: path.to_vec(),
+            start,
+            direction,
+            adjustment: spawn.adjustment.clone(),
+            outcome,
+        });
+    });
+    snaps
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        rma::{FVector, SpawnActorFeature},
+        voxel::{tunnel, CarveSettings},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_snap_to_floor() {
+        let spawn = |z, direction| {
+            RoomFeature::SpawnActorFeature(SpawnActorFeature {
+                location: FVector {
+                    x: 1000.0,
+                    y: 0.0,
+                    z,
+                },
+                adjustment: EItemAdjustmentType::Floor,
+                adjustment_direction: direction,
+                ..Default::default()
+            })
+        };
+        let down = FVector {
+            x: 0.0,
+            y: 0.0,
+            z: -1.0,
+        };
+        let room = RoomGenerator {
+            room_features: vec![
+                tunnel(0.0, 0.0, 2000.0),
+                spawn(200.0, down),
+                spawn(-1000.0, down),
+                spawn(200.0, FVector::default()),
+            ],
+            ..Default::default()
+        };
+        let grid = DensityGrid::carve(&room, &CarveSettings::default());
+
+        let snaps = simulate(&room, &grid, MAX_SNAP_DISTANCE);
+        // the spawn without a direction is not adjusted
+        assert_eq!(snaps.len(), 2);
+        let SnapOutcome::Hit { position, distance } = snaps[0].outcome else {
+            panic!("{:?}", snaps[0].outcome);
+        };
+        assert!((position.z + 100.0).abs() < 10.0);
+        assert!((distance - 300.0).abs() < 10.0);
+        assert_eq!(snaps[1].outcome, SnapOutcome::InRock);
+
+        let short = simulate(&room, &grid, 100.0);
+        assert_eq!(short[0].outcome, SnapOutcome::Missed);
+    }
+}
@@ -0,0 +1,310 @@
+//! Monte Carlo statistics over room variants.
+
+use std::io::Write;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use three_d_asset::vec3;
+
+use crate::{
+    bounds::Extent,
+    rma::{FRandRange, FVector, RoomFeature, RoomGenerator},
+    voxel::mid_or,
+};
+
+/// Measurements of a single generated variant.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RoomSample {
+    pub seed: i32,
+    /// Approximate carved volume in cubic units. Overlapping features are counted twice.
+    pub carved_volume: f32,
+    pub extent_x: f32,
+    pub extent_y: f32,
+    pub extent_z: f32,
+    pub entrances: u32,
+    pub spawn_actors: u32,
+    pub resources: u32,
+    pub resource_amount: f32,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Distribution {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    pub std_dev: f32,
+    pub median: f32,
+}
+
+impl Distribution {
+    pub fn new(values: impl IntoIterator<Item = f32>) -> Self {
+        let mut values = values.into_iter().collect::<Vec<_>>();
+        if values.is_empty() {
+            return Self::default();
+        }
+        values.sort_by(f32::total_cmp);
+        let n = values.len() as f32;
+        let mean = values.iter().sum::<f32>() / n;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n;
+        Self {
+            min: values[0],
+            max: values[values.len() - 1],
+            mean,
+            std_dev: variance.sqrt(),
+            median: values[values.len() / 2],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RoomStatistics {
+    pub carved_volume: Distribution,
+    pub extent_x: Distribution,
+    pub extent_y: Distribution,
+    pub extent_z: Distribution,
+    pub entrances: Distribution,
+    pub spawn_actors: Distribution,
+    pub resources: Distribution,
+    pub resource_amount: Distribution,
+    pub samples: Vec<RoomSample>,
+}
+
+fn mid(range: &FRandRange) -> f32 {
+    (range.min + range.max) / 2.0
+}
+
+fn distance(a: FVector, b: FVector) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+}
+
+/// Volume of a frustum with end cross-section areas `a1` and `a2`.
+fn frustum(length: f32, a1: f32, a2: f32) -> f32 {
+    length / 3.0 * (a1 + a2 + (a1 * a2).sqrt())
+}
+
+#[derive(Default)]
+struct Accumulator {
+    sample: RoomSample,
+    extent: Option<Extent>,
+}
+
+impl Accumulator {
+    fn extend(&mut self, center: FVector, radius: FVector) {
+        let e = Extent::around(center.into(), radius.into());
+        self.extent = Some(self.extent.map_or(e, |x| x.union(e)));
+    }
+
+    fn add(&mut self, features: &[RoomFeature]) {
+        use std::f32::consts::PI;
+
+        let point = FVector::default();
+        for feature in features {
+            match feature {
+                RoomFeature::FloodFillLine(line) => {
+                    // upper half elliptical cross-sections swept along the line, with a quarter
+                    // ellipsoid cap on each end
+                    let area = |h: f32, v: f32| PI * h * v / 2.0;
+                    for pair in line.points.windows(2) {
+                        let (p1, p2) = (&pair[0], &pair[1]);
+                        self.sample.carved_volume += frustum(
+                            distance(p1.location, p2.location),
+                            area(p1.h_range, p1.v_range),
+                            area(p2.h_range, p2.v_range),
+                        );
+                    }
+                    for p in [line.points.first(), line.points.last()]
+                        .into_iter()
+                        .flatten()
+                    {
+                        self.sample.carved_volume += PI * p.h_range * p.h_range * p.v_range / 3.0;
+                    }
+                    for p in &line.points {
+                        let r = FVector {
+                            x: p.h_range,
+                            y: p.h_range,
+                            z: p.v_range,
+                        };
+                        self.extend(p.location, r);
+                    }
+                }
+                RoomFeature::FloodFillPillar(pillar) => {
+                    // an unset scale is 1, as when carving
+                    let scale = mid_or(&pillar.range_scale, 1.0);
+                    let area = |r: f32| PI * r * r;
+                    for pair in pillar.points.windows(2) {
+                        let (p1, p2) = (&pair[0], &pair[1]);
+                        self.sample.carved_volume -= frustum(
+                            distance(p1.location, p2.location),
+                            area(mid(&p1.range) * scale),
+                            area(mid(&p2.range) * scale),
+                        );
+                    }
+                    for p in &pillar.points {
+                        let r = p.range.max * scale;
+                        self.extend(p.location, FVector { x: r, y: r, z: r });
+                    }
+                }
+                RoomFeature::FloodFillBox(b) => {
+                    let volume = 8.0 * b.extends.x * b.extends.y * b.extends.z;
+                    self.sample.carved_volume += if b.is_carver { volume } else { -volume };
+                    self.extend(b.position, b.extends);
+                }
+                RoomFeature::EntranceFeature(f) => {
+                    self.sample.entrances += 1;
+                    self.extend(f.location, point);
+                }
+                RoomFeature::SpawnActorFeature(f) => {
+                    self.sample.spawn_actors += 1;
+                    self.extend(f.location, point);
+                }
+                RoomFeature::ResourceFeature(f) => {
+                    self.sample.resources += 1;
+                    self.sample.resource_amount += f.base_amount;
+                    self.extend(f.location, point);
+                }
+                RoomFeature::DropPodCalldownLocationFeature(f) => self.extend(f.location, point),
+                _ => {}
+            }
+            self.add(feature.children());
+        }
+    }
+}
+
+impl RoomGenerator {
+    /// Measure the variant generated by `seed`, including sampled `FRandRange` values.
+    pub fn sample(&self, seed: i32) -> RoomSample {
+        let mut acc = Accumulator::default();
+        acc.add(&self.instantiate_sampled(seed).room_features);
+        let size = acc.extent.map_or(vec3(0.0, 0.0, 0.0), |e| e.size());
+        RoomSample {
+            seed,
+            carved_volume: acc.sample.carved_volume.max(0.0),
+            extent_x: size.x,
+            extent_y: size.y,
+            extent_z: size.z,
+            ..acc.sample
+        }
+    }
+
+    /// Sample `count` consecutive seeds starting at `first_seed`.
+    pub fn statistics(&self, first_seed: i32, count: u32) -> RoomStatistics {
+        let samples = (0..count)
+            .map(|i| self.sample(first_seed.wrapping_add(i as i32)))
+            .collect::<Vec<_>>();
+        let dist = |f: fn(&RoomSample) -> f32| Distribution::new(samples.iter().map(f));
+        RoomStatistics {
+            carved_volume: dist(|s| s.carved_volume),
+            extent_x: dist(|s| s.extent_x),
+            extent_y: dist(|s| s.extent_y),
+            extent_z: dist(|s| s.extent_z),
+            entrances: dist(|s| s.entrances as f32),
+            spawn_actors: dist(|s| s.spawn_actors as f32),
+            resources: dist(|s| s.resources as f32),
+            resource_amount: dist(|s| s.resource_amount),
+            samples,
+        }
+    }
+}
+
+impl RoomStatistics {
+    /// Write one row per sample.
+    pub fn write_csv<W: Write>(&self, mut w: W) -> Result<()> {
+        writeln!(
+            w,
+            "seed,carved_volume,extent_x,extent_y,extent_z,entrances,spawn_actors,resources,resource_amount"
+        )?;
+        for s in &self.samples {
+            writeln!(
+                w,
+                "{},{},{},{},{},{},{},{},{}",
+                s.seed,
+                s.carved_volume,
+                s.extent_x,
+                s.extent_y,
+                s.extent_z,
+                s.entrances,
+                s.spawn_actors,
+                s.resources,
+                s.resource_amount
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write one row per measurement with its distribution over all samples.
+    pub fn write_summary_csv<W: Write>(&self, mut w: W) -> Result<()> {
+        writeln!(w, "measurement,min,max,mean,std_dev,median")?;
+        for (name, d) in [
+            ("carved_volume", &self.carved_volume),
+            ("extent_x", &self.extent_x),
+            ("extent_y", &self.extent_y),
+            ("extent_z", &self.extent_z),
+            ("entrances", &self.entrances),
+            ("spawn_actors", &self.spawn_actors),
+            ("resources", &self.resources),
+            ("resource_amount", &self.resource_amount),
+        ] {
+            writeln!(
+                w,
+                "{name},{},{},{},{},{}",
+                d.min, d.max, d.mean, d.std_dev, d.median
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write the distributions along with every sample.
+    pub fn write_json<W: Write>(&self, w: W) -> Result<()> {
+        serde_json::to_writer_pretty(w, self)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        rma::{FRandLinePoint, FloodFillPillar},
+        voxel::tunnel,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_sample_line_and_pillar() {
+        let point = |z| FRandLinePoint {
+            location: FVector {
+                x: 1000.0,
+                y: 0.0,
+                z,
+            },
+            range: FRandRange {
+                min: 150.0,
+                max: 150.0,
+            },
+            ..Default::default()
+        };
+        let room = RoomGenerator {
+            room_features: vec![
+                tunnel(0.0, 0.0, 2000.0),
+                // range scale left unset
+                RoomFeature::FloodFillPillar(FloodFillPillar {
+                    points: vec![point(0.0), point(1000.0)],
+                    ..Default::default()
+                }),
+            ],
+            ..Default::default()
+        };
+        let sample = room.sample(0);
+
+        use std::f32::consts::PI;
+        // half elliptical tunnel with quarter ellipsoid caps, less the pillar's cylinder
+        let tunnel = 2000.0 * PI * 500.0 * 400.0 / 2.0 + 2.0 * PI * 500.0 * 500.0 * 400.0 / 3.0;
+        let pillar = 1000.0 * PI * 150.0 * 150.0;
+        let expected = tunnel - pillar;
+        assert!((sample.carved_volume - expected).abs() / expected < 1e-4);
+        assert_eq!(sample.extent_x, 3000.0);
+        assert_eq!(sample.extent_y, 1000.0);
+        assert_eq!(sample.extent_z, 400.0 + 1150.0);
+    }
+}
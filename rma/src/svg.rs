@@ -0,0 +1,504 @@
+//! Floor plan rendering of rooms to SVG, for images in places without a GPU.
+//!
+//! Corridors carved by `FloodFillLine`s and carving boxes are drawn as filled outlines, pillars and
+//! filling boxes as rock on top of them, followed by markers for point features. Geometry is
+//! drawn in room units inside a scaled group so the SVG stays easy to post-process.
+
+use std::{fmt::Write as _, io::Write};
+
+use anyhow::Result;
+use three_d_asset::{vec2, vec3, InnerSpace, Quat, Srgba, Vector2, Vector3};
+
+use crate::{
+    rma::{iter_features, FRoomLinePoint, FloodFillBox, RoomFeature, RoomGenerator},
+    voxel::mid_or,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    /// Looking down onto the XY plane.
+    Top,
+    /// Looking along Y onto the XZ plane.
+    Front,
+    /// Looking along X onto the YZ plane.
+    Side,
+}
+
+impl View {
+    pub fn name(self) -> &'static str {
+        match self {
+            View::Top => "Top (XY)",
+            View::Front => "Front (XZ)",
+            View::Side => "Side (YZ)",
+        }
+    }
+
+    /// Position in the view with Y pointing down, as in SVG. UE is left handed so looking down
+    /// with X to the right puts Y downwards.
+    fn project(self, v: Vector3<f32>) -> Vector2<f32> {
+        match self {
+            View::Top => vec2(v.x, v.y),
+            View::Front => vec2(v.x, -v.z),
+            View::Side => vec2(v.y, -v.z),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SvgOptions {
+    /// Views drawn side by side.
+    pub views: Vec<View>,
+    /// Pixels per room unit.
+    pub scale: f32,
+    pub title: Option<String>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            views: vec![View::Top],
+            scale: 0.1,
+            title: None,
+        }
+    }
+}
+
+/// Margin around every view in pixels, leaving room for labels.
+const MARGIN: f32 = 40.0;
+
+const CORRIDOR_FILL: &str = "#dccbb4";
+const CORRIDOR_STROKE: &str = "#6b5540";
+const ROCK_FILL: &str = "#8a7a6a";
+const ROCK_STROKE: &str = "#3b3028";
+
+fn color(c: Srgba) -> String {
+    format!("#{:02x}{:02x}{:02x}", c.r, c.g, c.b)
+}
+
+/// An axis aligned ellipse in view space.
+#[derive(Clone, Copy)]
+struct Ellipse {
+    center: Vector2<f32>,
+    radii: Vector2<f32>,
+}
+
+impl Ellipse {
+    /// Point of the ellipse furthest in direction `n`.
+    fn support(&self, n: Vector2<f32>) -> Vector2<f32> {
+        let (a, b) = (self.radii.x, self.radii.y);
+        let d = vec2(a * a * n.x, b * b * n.y);
+        let len = (a * a * n.x * n.x + b * b * n.y * n.y).sqrt();
+        if len > 0.0 {
+            self.center + d / len
+        } else {
+            self.center
+        }
+    }
+}
+
+/// Union of ellipses and polygons, drawn as a single outlined region.
+#[derive(Default)]
+struct Region {
+    ellipses: Vec<Ellipse>,
+    polygons: Vec<Vec<Vector2<f32>>>,
+}
+
+impl Region {
+    /// Chain of ellipses joined by their tangent hulls, like a swept outline.
+    fn sweep(&mut self, ellipses: &[Ellipse]) {
+        self.ellipses.extend_from_slice(ellipses);
+        for pair in ellipses.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let d = b.center - a.center;
+            if d.magnitude2() == 0.0 {
+                continue;
+            }
+            let n = vec2(-d.y, d.x).normalize();
+            self.polygons.push(vec![
+                a.support(n),
+                b.support(n),
+                b.support(-n),
+                a.support(-n),
+            ]);
+        }
+    }
+
+    fn shapes(&self, out: &mut String) {
+        for e in &self.ellipses {
+            let _ = write!(
+                out,
+                r#"<ellipse cx="{:.1}" cy="{:.1}" rx="{:.1}" ry="{:.1}"/>"#,
+                e.center.x, e.center.y, e.radii.x, e.radii.y
+            );
+        }
+        for p in &self.polygons {
+            polygon(out, p, "");
+        }
+    }
+
+    /// Outline of the union: everything stroked first and then filled on top, hiding the
+    /// strokes inside.
+    fn draw(&self, out: &mut String, fill: &str, stroke: &str, dashed: bool) {
+        let dash = if dashed {
+            r#" stroke-dasharray="6 4""#
+        } else {
+            ""
+        };
+        let _ = write!(
+            out,
+            r#"<g fill="{fill}" stroke="{stroke}" stroke-width="4"{dash} vector-effect="non-scaling-stroke">"#
+        );
+        self.shapes(out);
+        let _ = write!(out, r#"</g><g fill="{fill}" stroke="none">"#);
+        self.shapes(out);
+        out.push_str("</g>");
+    }
+}
+
+fn polygon(out: &mut String, points: &[Vector2<f32>], attributes: &str) {
+    let points = points
+        .iter()
+        .map(|p| format!("{:.1},{:.1}", p.x, p.y))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let _ = write!(out, r#"<polygon points="{points}"{attributes}/>"#);
+}
+
+/// Convex hull, counter-clockwise in view space.
+fn hull(mut points: Vec<Vector2<f32>>) -> Vec<Vector2<f32>> {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    let cross = |o: Vector2<f32>, a: Vector2<f32>, b: Vector2<f32>| {
+        (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+    };
+    let mut hull: Vec<Vector2<f32>> = vec![];
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for p in pass {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+/// Cross-section of a line point as seen in `view`.
+fn line_section(view: View, point: &FRoomLinePoint) -> Ellipse {
+    let profile = point.profile();
+    let top = profile
+        .ceiling
+        .map_or(profile.height, |c| c.min(profile.height));
+    let location: Vector3<f32> = point.location.into();
+    match view {
+        View::Top => Ellipse {
+            center: view.project(location),
+            radii: vec2(profile.width, profile.width),
+        },
+        View::Front | View::Side => Ellipse {
+            center: view.project(location + vec3(0.0, 0.0, (top - profile.floor_depth) / 2.0)),
+            radii: vec2(profile.width, (top + profile.floor_depth) / 2.0),
+        },
+    }
+}
+
+fn box_outline(view: View, b: &FloodFillBox) -> Vec<Vector2<f32>> {
+    hull(b.corners(0.0).map(|c| view.project(c)).to_vec())
+}
+
+/// Shapes of one view, in room units.
+#[derive(Default)]
+struct Layers {
+    carved: Region,
+    filled: Region,
+    filled_boxes: Region,
+    /// Markers of point features as SVG elements, drawn on top of everything else.
+    markers: String,
+    min: Option<Vector2<f32>>,
+    max: Option<Vector2<f32>>,
+}
+
+impl Layers {
+    fn extend(&mut self, center: Vector2<f32>, radii: Vector2<f32>) {
+        let (lo, hi) = (center - radii, center + radii);
+        let min = self.min.get_or_insert(lo);
+        *min = vec2(min.x.min(lo.x), min.y.min(lo.y));
+        let max = self.max.get_or_insert(hi);
+        *max = vec2(max.x.max(hi.x), max.y.max(hi.y));
+    }
+
+    fn new(room: &RoomGenerator, view: View) -> Self {
+        let mut layers = Layers::default();
+        let r = |r: f32| vec2(r, r);
+        iter_features(
+            &room.room_features,
+            &mut vec![],
+            &mut |feature, _| match feature {
+                RoomFeature::FloodFillLine(line) => {
+                    let sections = line
+                        .points
+                        .iter()
+                        .map(|p| line_section(view, p))
+                        .collect::<Vec<_>>();
+                    for s in &sections {
+                        layers.extend(s.center, s.radii);
+                    }
+                    layers.carved.sweep(&sections);
+                }
+                RoomFeature::FloodFillPillar(pillar) => {
+                    let scale = mid_or(&pillar.range_scale, 1.0);
+                    let sections = pillar
+                        .points
+                        .iter()
+                        .map(|p| Ellipse {
+                            center: view.project(p.location.into()),
+                            radii: r(mid_or(&p.range, 0.0) * scale),
+                        })
+                        .collect::<Vec<_>>();
+                    for s in &sections {
+                        layers.extend(s.center, s.radii);
+                    }
+                    layers.filled.sweep(&sections);
+                }
+                RoomFeature::FloodFillBox(b) => {
+                    let outline = box_outline(view, b);
+                    for &p in &outline {
+                        layers.extend(p, r(0.0));
+                    }
+                    if b.is_carver {
+                        layers.carved.polygons.push(outline);
+                    } else {
+                        layers.filled_boxes.polygons.push(outline);
+                    }
+                }
+                RoomFeature::EntranceFeature(f) => {
+                    let c = view.project(f.location.into());
+                    let rotation: Quat = f.direction.into();
+                    let tip =
+                        view.project(Vector3::from(f.location) + rotation * vec3(300.0, 0.0, 0.0));
+                    let _ = write!(
+                        layers.markers,
+                        r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="3" vector-effect="non-scaling-stroke"/><circle cx="{:.1}" cy="{:.1}" r="150" fill="{}" stroke="black" vector-effect="non-scaling-stroke"/>"#,
+                        c.x,
+                        c.y,
+                        tip.x,
+                        tip.y,
+                        color(f.entrance_type.color()),
+                        c.x,
+                        c.y,
+                        color(f.entrance_type.color())
+                    );
+                    layers.extend(c, r(150.0));
+                }
+                RoomFeature::SpawnActorFeature(f) => {
+                    let c = view.project(f.location.into());
+                    polygon(
+                        &mut layers.markers,
+                        &[
+                            c + vec2(0.0, -120.0),
+                            c + vec2(104.0, 60.0),
+                            c + vec2(-104.0, 60.0),
+                        ],
+                        r##" fill="#ffc800" stroke="black" vector-effect="non-scaling-stroke""##,
+                    );
+                    layers.extend(c, r(120.0));
+                }
+                RoomFeature::DropPodCalldownLocationFeature(f) => {
+                    let c = view.project(f.location.into());
+                    polygon(
+                        &mut layers.markers,
+                        &[
+                            c + vec2(0.0, -200.0),
+                            c + vec2(200.0, 0.0),
+                            c + vec2(0.0, 200.0),
+                            c + vec2(-200.0, 0.0),
+                        ],
+                        r##" fill="#00ff00" stroke="black" vector-effect="non-scaling-stroke""##,
+                    );
+                    let _ = write!(
+                        layers.markers,
+                        r#"<circle cx="{:.1}" cy="{:.1}" r="80" fill="none" stroke="black" vector-effect="non-scaling-stroke"/>"#,
+                        c.x, c.y
+                    );
+                    layers.extend(c, r(200.0));
+                }
+                RoomFeature::ResourceFeature(f) => {
+                    let c = view.project(f.location.into());
+                    let _ = write!(
+                        layers.markers,
+                        r##"<circle cx="{:.1}" cy="{:.1}" r="60" fill="#b040ff" stroke="black" vector-effect="non-scaling-stroke"/>"##,
+                        c.x, c.y
+                    );
+                    layers.extend(c, r(60.0));
+                }
+                _ => {}
+            },
+        );
+        if room.base.bounds > 0.0 {
+            layers.extend(vec2(0.0, 0.0), r(room.base.bounds));
+        }
+        layers
+    }
+}
+
+/// Round down to 1, 2 or 5 times a power of ten, a meter if there is no length to round.
+fn nice(length: f32) -> f32 {
+    if length <= 0.0 || !length.is_finite() {
+        return 100.0;
+    }
+    let power = 10f32.powf(length.log10().floor());
+    [5.0, 2.0, 1.0]
+        .into_iter()
+        .map(|m| m * power)
+        .find(|&l| l <= length)
+        .unwrap_or(power)
+}
+
+impl RoomGenerator {
+    pub fn write_svg<W: Write>(&self, options: &SvgOptions, mut w: W) -> Result<()> {
+        let title_height = if options.title.is_some() { 30.0 } else { 0.0 };
+        let mut body = String::new();
+        let (mut width, mut height) = (0.0f32, 0.0f32);
+
+        for &view in &options.views {
+            let layers = Layers::new(self, view);
+            let min = layers.min.unwrap_or(vec2(-500.0, -500.0));
+            let max = layers.max.unwrap_or(vec2(500.0, 500.0));
+            let size = (max - min) * options.scale + vec2(MARGIN, MARGIN) * 2.0;
+            let origin = vec2(width + MARGIN, title_height + MARGIN) - min * options.scale;
+
+            let _ = write!(
+                body,
+                r#"<text x="{:.1}" y="{:.1}" font-family="sans-serif" font-size="14">{}</text>"#,
+                width + MARGIN,
+                title_height + 24.0,
+                view.name()
+            );
+            let _ = write!(
+                body,
+                r#"<g transform="translate({:.1} {:.1}) scale({})">"#,
+                origin.x, origin.y, options.scale
+            );
+            if self.base.bounds > 0.0 {
+                let _ = write!(
+                    body,
+                    r##"<circle cx="0" cy="0" r="{:.1}" fill="none" stroke="#888888" stroke-width="1.5" stroke-dasharray="8 6" vector-effect="non-scaling-stroke"/>"##,
+                    self.base.bounds
+                );
+            }
+            layers
+                .carved
+                .draw(&mut body, CORRIDOR_FILL, CORRIDOR_STROKE, false);
+            layers.filled.draw(&mut body, ROCK_FILL, ROCK_STROKE, false);
+            layers
+                .filled_boxes
+                .draw(&mut body, ROCK_FILL, ROCK_STROKE, true);
+            body.push_str(&layers.markers);
+            body.push_str("</g>");
+
+            // scale bar along the bottom of the view
+            let length = nice((max.x - min.x) / 4.0);
+            let (x, y) = (width + MARGIN, title_height + size.y - 16.0);
+            let end = x + length * options.scale;
+            let _ = write!(
+                body,
+                r#"<path d="M{x:.1} {:.1} V{y:.1} H{end:.1} V{:.1}" fill="none" stroke="black" stroke-width="2"/><text x="{:.1}" y="{:.1}" font-family="sans-serif" font-size="12">{} m</text>"#,
+                y - 5.0,
+                y - 5.0,
+                end + 6.0,
+                y + 4.0,
+                length / 100.0
+            );
+
+            width += size.x;
+            height = height.max(title_height + size.y);
+        }
+
+        writeln!(
+            w,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" viewBox="0 0 {:.0} {:.0}">"#,
+            width.ceil(),
+            height.ceil(),
+            width.ceil(),
+            height.ceil()
+        )?;
+        writeln!(w, r#"<rect width="100%" height="100%" fill="white"/>"#)?;
+        if let Some(title) = &options.title {
+            writeln!(
+                w,
+                r#"<text x="{MARGIN}" y="22" font-family="sans-serif" font-size="18" font-weight="bold">{}</text>"#,
+                escape(title)
+            )?;
+        }
+        writeln!(w, "{body}")?;
+        writeln!(w, "</svg>")?;
+        Ok(())
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{rma::RoomGeneratorBase, voxel::tunnel};
+
+    use super::*;
+
+    #[test]
+    fn test_nice() {
+        for (length, expected) in [
+            (750.0, 500.0),
+            (2000.0, 2000.0),
+            (199.0, 100.0),
+            (3.5, 2.0),
+            (0.0, 100.0),
+            (-5.0, 100.0),
+            (f32::NAN, 100.0),
+            (f32::INFINITY, 100.0),
+        ] {
+            let nice = nice(length);
+            assert!(
+                (nice - expected).abs() <= expected * 1e-5,
+                "{length}: {nice}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_svg() -> Result<()> {
+        let room = RoomGenerator {
+            base: RoomGeneratorBase {
+                bounds: 3000.0,
+                ..Default::default()
+            },
+            room_features: vec![tunnel(0.0, 0.0, 2000.0)],
+        };
+        let options = SvgOptions {
+            views: vec![View::Top, View::Side],
+            title: Some("Tunnel <1>".to_string()),
+            ..Default::default()
+        };
+        let mut bytes = vec![];
+        room.write_svg(&options, &mut bytes)?;
+        let svg = String::from_utf8(bytes)?;
+
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains("Tunnel &lt;1&gt;"));
+        assert!(svg.contains("Top (XY)") && svg.contains("Side (YZ)"));
+        // bounds circle in each view
+        assert_eq!(
+            svg.matches(r#"<circle cx="0" cy="0" r="3000.0""#).count(),
+            2
+        );
+        assert!(svg.contains(CORRIDOR_FILL));
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+//! Surface meshes lofted through the cross-sections of lines and pillars, an easier to read
+//! alternative to their wireframes.
+
+use three_d_asset::{vec3, InnerSpace, Vector3, VectorSpace};
+
+use crate::{
+    mesh::SurfaceMesh,
+    rma::{FloodFillLine, FloodFillPillar},
+    wireframe::SECTION_STEPS,
+};
+
+/// Sections interpolated along each segment of a line.
+const LINE_SUBDIVISIONS: usize = 4;
+
+/// Closed mesh through `rings` of matching points, capped at both ends. Rings must turn
+/// counter-clockwise when looking along the tube for the normals to face outwards.
+fn loft(rings: &[Vec<Vector3<f32>>]) -> SurfaceMesh {
+    let mut mesh = SurfaceMesh::default();
+    let (Some(first), Some(last)) = (rings.first(), rings.last()) else {
+        return mesh;
+    };
+    let n = first.len() as u32;
+    for ring in rings {
+        mesh.positions.extend(ring);
+    }
+    for i in 0..rings.len() as u32 - 1 {
+        for k in 0..n {
+            let a = i * n + k;
+            let b = i * n + (k + 1) % n;
+            let (c, d) = (b + n, a + n);
+            mesh.indices.extend([a, b, d, b, c, d]);
+        }
+    }
+
+    // caps get their own vertices so they are shaded flat
+    for (ring, end) in [(first, false), (last, true)] {
+        let center = mesh.positions.len() as u32;
+        mesh.positions
+            .push(ring.iter().fold(vec3(0.0, 0.0, 0.0), |a, &p| a + p) / ring.len() as f32);
+        mesh.positions.extend(ring);
+        for k in 0..n {
+            let (a, b) = (center + 1 + k, center + 1 + (k + 1) % n);
+            mesh.indices
+                .extend(if end { [center, a, b] } else { [center, b, a] });
+        }
+    }
+
+    // area weighted face normals
+    mesh.normals = vec![vec3(0.0, 0.0, 0.0); mesh.positions.len()];
+    for tri in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| mesh.positions[i as usize]);
+        let normal = (b - a).cross(c - a);
+        for &i in tri {
+            mesh.normals[i as usize] += normal;
+        }
+    }
+    for normal in &mut mesh.normals {
+        if normal.magnitude2() > 0.0 {
+            *normal = normal.normalize();
+        }
+    }
+    mesh
+}
+
+impl FloodFillLine {
+    /// The nominal carved tunnel, lofted through cross-sections interpolated between points.
+    pub fn tube(&self) -> SurfaceMesh {
+        let sides = self.sides();
+        let section = |i: usize, j: usize, t: f32| {
+            let (a, b) = (&self.points[i], &self.points[j]);
+            let location = Vector3::from(a.location).lerp(b.location.into(), t);
+            let side = sides[i].lerp(sides[j], t).normalize();
+            a.profile()
+                .lerp(&b.profile(), t)
+                .outline(0.0, SECTION_STEPS)
+                .into_iter()
+                .map(|p| location + side * p.x + vec3(0.0, 0.0, p.y))
+                .collect::<Vec<_>>()
+        };
+        let mut rings = vec![];
+        for i in 0..self.points.len().saturating_sub(1) {
+            for s in 0..LINE_SUBDIVISIONS {
+                rings.push(section(i, i + 1, s as f32 / LINE_SUBDIVISIONS as f32));
+            }
+        }
+        if let Some(last) = self.points.len().checked_sub(1) {
+            rings.push(section(last, last, 0.0));
+        }
+        loft(&rings)
+    }
+}
+
+impl FloodFillPillar {
+    /// Smallest and largest rock the pillar can fill, from the point ranges scaled by
+    /// `range_scale` and `endcap_scale` at the ends. Noise is not included.
+    pub fn tubes(&self) -> [SurfaceMesh; 2] {
+        let frames = self.frames();
+        let tube = |radius: &dyn Fn(usize) -> f32| {
+            let rings = self
+                .points
+                .iter()
+                .zip(&frames)
+                .enumerate()
+                .map(|(i, (point, &(u, v)))| {
+                    (0..SECTION_STEPS)
+                        .map(|k| {
+                            let angle =
+                                2.0 * std::f32::consts::PI * k as f32 / SECTION_STEPS as f32;
+                            Vector3::from(point.location)
+                                + (u * angle.cos() + v * angle.sin()) * radius(i)
+                        })
+                        .collect()
+                })
+                .collect::<Vec<_>>();
+            loft(&rings)
+        };
+        [
+            tube(&|i| self.radius_range(i).0),
+            tube(&|i| self.radius_range(i).1),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rma::{FRandLinePoint, FRandRange, FVector};
+
+    use super::*;
+
+    /// Volume enclosed by `mesh`, positive if its triangles face outwards.
+    fn volume(mesh: &SurfaceMesh) -> f32 {
+        mesh.indices
+            .chunks_exact(3)
+            .map(|tri| {
+                let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| mesh.positions[i as usize]);
+                a.dot(b.cross(c)) / 6.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_pillar_tubes() {
+        let point = |z| FRandLinePoint {
+            location: FVector { x: 0.0, y: 0.0, z },
+            range: FRandRange {
+                min: 100.0,
+                max: 200.0,
+            },
+            ..Default::default()
+        };
+        let pillar = FloodFillPillar {
+            points: vec![point(0.0), point(500.0), point(1000.0)],
+            ..Default::default()
+        };
+        let [min, max] = pillar.tubes();
+        let cylinder = |r: f32| std::f32::consts::PI * r * r * 1000.0;
+        assert!((volume(&min) / cylinder(100.0) - 1.0).abs() < 0.01);
+        assert!((volume(&max) / cylinder(200.0) - 1.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,621 @@
+//! Voxel approximation of the cave volume carved out by a room's flood fill features.
+//!
+//! Every carving feature (`FloodFillLine`, carving `FloodFillBox`) is applied first, then every
+//! filling feature (`FloodFillPillar`, non carving `FloodFillBox`) puts rock back. Each feature is
+//! described by an approximate signed distance function displaced by noise, so the result is a
+//! smooth field that can be meshed or queried.
+
+use three_d_asset::{vec2, vec3, InnerSpace, Quat, Vector2, Vector3};
+
+use crate::rma::{
+    FRandRange, FRoomLinePoint, FVector, FloodFillBox, FloodFillLine, FloodFillPillar, RoomFeature,
+    RoomGenerator, UFloodFillSettings,
+};
+
+#[derive(Debug, Clone)]
+pub struct CarveSettings {
+    /// Edge length of a voxel.
+    pub voxel_size: f32,
+    /// Noise displacing feature surfaces by their noise ranges, except where a feature overrides
+    /// it with noise of its own.
+    pub noise: UFloodFillSettings,
+}
+
+impl Default for CarveSettings {
+    fn default() -> Self {
+        Self {
+            voxel_size: 100.0,
+            noise: UFloodFillSettings {
+                noise_size: FVector {
+                    x: 400.0,
+                    y: 400.0,
+                    z: 400.0,
+                },
+                freq_multiplier: 2.0,
+                amplitude_multiplier: 0.5,
+                min_value: -1.0,
+                max_value: 1.0,
+                octaves: 3,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Cross-section of a `FloodFillLine` at one of its points, relative to the point's location.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LineProfile {
+    /// Horizontal radius.
+    pub width: f32,
+    /// Height of the vaulted ceiling above the location.
+    pub height: f32,
+    /// Height of a flat ceiling cutting off the vault, if any.
+    pub ceiling: Option<f32>,
+    /// Depth of the floor below the location. Zero gives a flat floor through the location.
+    pub floor_depth: f32,
+    /// Rise of the floor per unit of sideways offset to the left of the line.
+    pub floor_slope: f32,
+    pub wall_noise: f32,
+    pub ceiling_noise: f32,
+    pub floor_noise: f32,
+}
+
+impl FRoomLinePoint {
+    /// Interpret the point's parameters as a cross-section. The meaning of the ceiling and floor
+    /// parameters is inferred from how rooms use them: `v_range` scaled by `height_scale` is the
+    /// vault height, `cielingheight` flattens the vault and `floor_angle` (degrees) tilts the
+    /// floor sideways.
+    pub fn profile(&self) -> LineProfile {
+        let height_scale = if self.height_scale > 0.0 {
+            self.height_scale
+        } else {
+            1.0
+        };
+        LineProfile {
+            width: self.h_range,
+            height: self.v_range * height_scale,
+            ceiling: (self.cielingheight > 0.0).then_some(self.cielingheight),
+            floor_depth: self.floor_depth.max(0.0),
+            floor_slope: self.floor_angle.to_radians().tan(),
+            wall_noise: self.wall_noise_range,
+            ceiling_noise: self.cieling_noise_range,
+            floor_noise: self.floor_noise_range,
+        }
+    }
+}
+
+/// Approximate signed distance to an axis aligned ellipse with radii `ra` and `rb`.
+fn ellipse(a: f32, b: f32, ra: f32, rb: f32) -> f32 {
+    let (ra, rb) = (ra.max(1.0), rb.max(1.0));
+    (((a / ra).powi(2) + (b / rb).powi(2)).sqrt() - 1.0) * ra.min(rb)
+}
+
+impl LineProfile {
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        Self {
+            width: lerp(self.width, other.width),
+            height: lerp(self.height, other.height),
+            ceiling: (self.ceiling.is_some() || other.ceiling.is_some()).then(|| {
+                lerp(
+                    self.ceiling.unwrap_or(self.height),
+                    other.ceiling.unwrap_or(other.height),
+                )
+            }),
+            floor_depth: lerp(self.floor_depth, other.floor_depth),
+            floor_slope: lerp(self.floor_slope, other.floor_slope),
+            wall_noise: lerp(self.wall_noise, other.wall_noise),
+            ceiling_noise: lerp(self.ceiling_noise, other.ceiling_noise),
+            floor_noise: lerp(self.floor_noise, other.floor_noise),
+        }
+    }
+
+    /// Box the profile fits in, noise included, relative to its point.
+    pub fn bounds(&self) -> (Vector3<f32>, Vector3<f32>) {
+        let width = self.width + self.wall_noise.abs();
+        let up = self.height.max(self.ceiling.unwrap_or(0.0)) + self.ceiling_noise.abs();
+        let down = self.floor_depth + self.floor_noise.abs() + width * self.floor_slope.abs();
+        (-vec3(width, width, down), vec3(width, width, up))
+    }
+
+    /// Height of the floor at sideways offset `side`, before noise.
+    pub fn floor(&self, side: f32) -> f32 {
+        side * self.floor_slope - self.floor_depth
+    }
+
+    /// Approximate signed distance from the boundary, negative inside. `horizontal` is the
+    /// horizontal distance from the location, `side` its signed component to the left of the
+    /// line and `z` the height above the location. `noise` is in [-1, 1].
+    pub fn distance(&self, horizontal: f32, side: f32, z: f32, noise: f32) -> f32 {
+        self.distance_with(horizontal, side, z, [noise; 3])
+    }
+
+    /// Like [`LineProfile::distance`] with separate wall, ceiling and floor noise.
+    pub fn distance_with(&self, horizontal: f32, side: f32, z: f32, noise: [f32; 3]) -> f32 {
+        let [wall, ceiling_noise, floor] = noise;
+        let width = self.width + self.wall_noise * wall;
+        let floor_depth = self.floor_depth + self.floor_noise * floor;
+        let mut d = if z >= 0.0 {
+            ellipse(
+                horizontal,
+                z,
+                width,
+                self.height + self.ceiling_noise * ceiling_noise,
+            )
+        } else if floor_depth > 1.0 {
+            ellipse(horizontal, z, width, floor_depth)
+        } else {
+            (horizontal - width).max(-z)
+        };
+        if let Some(ceiling) = self.ceiling {
+            d = d.max(z - (ceiling + self.ceiling_noise * ceiling_noise));
+        }
+        if self.floor_slope != 0.0 {
+            d = d.max(side * self.floor_slope - floor_depth - z);
+        }
+        d
+    }
+
+    /// Boundary of the cross-section perpendicular to the line as `(side, z)` points, with the
+    /// noise fixed at `noise`. The outline is closed, sampled at `steps` evenly spaced angles
+    /// starting to the left of the line and turning upwards, so outlines of different profiles
+    /// correspond point by point.
+    pub fn outline(&self, noise: f32, steps: usize) -> Vec<Vector2<f32>> {
+        let distance = |p: Vector2<f32>| self.distance(p.x.abs(), p.x, p.y, noise);
+        // the section is convex, so bisect along rays from a point inside it
+        let top = self.ceiling.map_or(self.height, |c| c.min(self.height));
+        let center = vec2(0.0, top.max(0.0) / 4.0);
+        let reach = 2.0
+            * (self.width
+                + self.height.max(self.ceiling.unwrap_or(0.0))
+                + self.floor_depth
+                + self.wall_noise.abs()
+                + self.ceiling_noise.abs()
+                + self.floor_noise.abs())
+            * (1.0 + self.floor_slope.abs())
+            + 1.0;
+        (0..steps)
+            .map(|i| {
+                let angle = 2.0 * std::f32::consts::PI * i as f32 / steps as f32;
+                let dir = vec2(angle.cos(), angle.sin());
+                let (mut lo, mut hi) = (0.0, reach);
+                for _ in 0..24 {
+                    let mid = (lo + hi) / 2.0;
+                    if distance(center + dir * mid) < 0.0 {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                center + dir * lo
+            })
+            .collect()
+    }
+}
+
+/// Midpoint of a range, treating an unset range as `default`.
+pub(crate) fn mid_or(range: &FRandRange, default: f32) -> f32 {
+    if range.min == 0.0 && range.max == 0.0 {
+        default
+    } else {
+        (range.min + range.max) / 2.0
+    }
+}
+
+#[derive(Clone, Copy)]
+struct PillarPoint {
+    location: Vector3<f32>,
+    radius: f32,
+    noise: f32,
+}
+
+/// Noise of a shape replacing [`CarveSettings::noise`], `None` where the room does not override
+/// it.
+type Override<'a> = Option<&'a UFloodFillSettings>;
+
+enum Shape<'a> {
+    /// Segment of a line with its wall, ceiling and floor noise.
+    Line(&'a FRoomLinePoint, &'a FRoomLinePoint, [Override<'a>; 3]),
+    Pillar(PillarPoint, PillarPoint, Override<'a>),
+    Box(&'a FloodFillBox),
+}
+
+fn min(a: Vector3<f32>, b: Vector3<f32>) -> Vector3<f32> {
+    vec3(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z))
+}
+
+fn max(a: Vector3<f32>, b: Vector3<f32>) -> Vector3<f32> {
+    vec3(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z))
+}
+
+impl Shape<'_> {
+    fn carves(&self) -> bool {
+        match self {
+            Shape::Line(..) => true,
+            Shape::Pillar(..) => false,
+            Shape::Box(b) => b.is_carver,
+        }
+    }
+
+    fn bounds(&self) -> (Vector3<f32>, Vector3<f32>) {
+        match self {
+            Shape::Line(a, b, _) => {
+                let point = |p: &FRoomLinePoint| {
+                    let (lo, hi) = p.profile().bounds();
+                    let location = Vector3::from(p.location);
+                    (location + lo, location + hi)
+                };
+                let (a, b) = (point(a), point(b));
+                (min(a.0, b.0), max(a.1, b.1))
+            }
+            Shape::Pillar(a, b, _) => {
+                let point = |p: &PillarPoint| {
+                    let r = p.radius + p.noise.abs();
+                    (p.location - vec3(r, r, r), p.location + vec3(r, r, r))
+                };
+                let (a, b) = (point(a), point(b));
+                (min(a.0, b.0), max(a.1, b.1))
+            }
+            Shape::Box(b) => {
+                // bounding sphere of the rotated box
+                let r = Vector3::from(b.extends).magnitude() + b.noise_range.abs();
+                let position = Vector3::from(b.position);
+                (position - vec3(r, r, r), position + vec3(r, r, r))
+            }
+        }
+    }
+
+    /// Signed distance from the shape's surface, negative inside. `noise` is the value of
+    /// [`CarveSettings::noise`] at `p`, used wherever the shape does not override it.
+    fn distance(&self, p: Vector3<f32>, noise: f32) -> f32 {
+        let noise = |settings: Override| {
+            settings.map_or(noise, |s| {
+                s.sample(FVector {
+                    x: p.x,
+                    y: p.y,
+                    z: p.z,
+                })
+            })
+        };
+        match self {
+            Shape::Line(p1, p2, overrides) => {
+                let (a, b) = (Vector3::from(p1.location), Vector3::from(p2.location));
+                let ab = b - a;
+                let t = if ab.magnitude2() > 0.0 {
+                    ((p - a).dot(ab) / ab.magnitude2()).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let d = p - (a + ab * t);
+                let dir = vec2(ab.x, ab.y);
+                let side = if dir.magnitude2() > 0.0 {
+                    let dir = dir.normalize();
+                    d.y * dir.x - d.x * dir.y
+                } else {
+                    d.x
+                };
+                let horizontal = vec2(d.x, d.y).magnitude();
+                p1.profile().lerp(&p2.profile(), t).distance_with(
+                    horizontal,
+                    side,
+                    d.z,
+                    overrides.map(noise),
+                )
+            }
+            Shape::Pillar(a, b, settings) => {
+                let ab = b.location - a.location;
+                let t = if ab.magnitude2() > 0.0 {
+                    ((p - a.location).dot(ab) / ab.magnitude2()).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let lerp = |x: f32, y: f32| x + (y - x) * t;
+                (p - (a.location + ab * t)).magnitude()
+                    - lerp(a.radius, b.radius)
+                    - lerp(a.noise, b.noise) * noise(*settings)
+            }
+            Shape::Box(b) => {
+                let rotation: Quat = b.rotation.into();
+                let local = rotation.conjugate() * (p - Vector3::from(b.position));
+                let q = vec3(
+                    local.x.abs() - b.extends.x,
+                    local.y.abs() - b.extends.y,
+                    local.z.abs() - b.extends.z,
+                );
+                max(q, vec3(0.0, 0.0, 0.0)).magnitude() + q.x.max(q.y).max(q.z).min(0.0)
+                    - b.noise_range * noise(b.noise.as_ref())
+            }
+        }
+    }
+}
+
+fn line_shapes<'a>(line: &'a FloodFillLine, shapes: &mut Vec<Shape<'a>>) {
+    // `flood_noise_override` is taken to be the floor's, as it is the only one left
+    let overrides = [
+        line.wall_noise_override.as_ref(),
+        line.ceiling_noise_override.as_ref(),
+        line.flood_noise_override.as_ref(),
+    ];
+    match line.points.as_slice() {
+        [] => {}
+        [p] => shapes.push(Shape::Line(p, p, overrides)),
+        points => {
+            for pair in points.windows(2) {
+                shapes.push(Shape::Line(&pair[0], &pair[1], overrides));
+            }
+        }
+    }
+}
+
+fn pillar_shapes<'a>(pillar: &'a FloodFillPillar, shapes: &mut Vec<Shape<'a>>) {
+    let range_scale = mid_or(&pillar.range_scale, 1.0);
+    let noise_scale = mid_or(&pillar.noise_range_scale, 1.0);
+    let endcap_scale = mid_or(&pillar.endcap_scale, 1.0);
+    let last = pillar.points.len().saturating_sub(1);
+    let points = pillar
+        .points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let endcap = if i == 0 || i == last {
+                endcap_scale
+            } else {
+                1.0
+            };
+            PillarPoint {
+                location: p.location.into(),
+                radius: mid_or(&p.range, 0.0) * range_scale * endcap,
+                noise: mid_or(&p.noise_range, 0.0) * noise_scale,
+            }
+        })
+        .collect::<Vec<_>>();
+    for pair in points.windows(2) {
+        shapes.push(Shape::Pillar(
+            pair[0],
+            pair[1],
+            pillar.noise_override.as_ref(),
+        ));
+    }
+}
+
+fn collect_shapes<'a>(features: &'a [RoomFeature], shapes: &mut Vec<Shape<'a>>) {
+    for feature in features {
+        match feature {
+            RoomFeature::FloodFillLine(line) => line_shapes(line, shapes),
+            RoomFeature::FloodFillPillar(pillar) => pillar_shapes(pillar, shapes),
+            RoomFeature::FloodFillBox(b) => shapes.push(Shape::Box(b)),
+            _ => {}
+        }
+        collect_shapes(feature.children(), shapes);
+    }
+}
+
+/// Signed distance field of the cave sampled on a regular grid.
+#[derive(Debug, Clone)]
+pub struct DensityGrid {
+    /// World position of the first voxel.
+    pub origin: Vector3<f32>,
+    pub voxel_size: f32,
+    /// Number of voxels along each axis.
+    pub dims: [usize; 3],
+    /// Approximate distance to the cave surface at each voxel with x varying fastest. Negative
+    /// values are open space, positive values are rock.
+    pub values: Vec<f32>,
+}
+
+impl DensityGrid {
+    /// Carve every flood fill feature in `room`. Features below a `RandomSelector` are all
+    /// carved, so pass an instantiated room to get a single variant.
+    ///
+    /// Panics if the voxel size is not positive.
+    pub fn carve(room: &RoomGenerator, settings: &CarveSettings) -> Self {
+        assert!(
+            settings.voxel_size > 0.0,
+            "voxel size must be positive, got {}",
+            settings.voxel_size
+        );
+        let mut shapes = vec![];
+        collect_shapes(&room.room_features, &mut shapes);
+
+        let voxel_size = settings.voxel_size;
+        let Some((lo, hi)) = shapes
+            .iter()
+            .map(Shape::bounds)
+            .reduce(|a, b| (min(a.0, b.0), max(a.1, b.1)))
+        else {
+            return Self {
+                origin: vec3(0.0, 0.0, 0.0),
+                voxel_size,
+                dims: [0; 3],
+                values: vec![],
+            };
+        };
+
+        let margin = vec3(voxel_size, voxel_size, voxel_size) * 2.0;
+        let origin = lo - margin;
+        let size = hi + margin - origin;
+        let dims = [size.x, size.y, size.z].map(|s| (s / voxel_size).ceil() as usize + 1);
+        let mut grid = Self {
+            origin,
+            voxel_size,
+            dims,
+            values: vec![voxel_size; dims[0] * dims[1] * dims[2]],
+        };
+
+        let mut noise = vec![f32::NAN; grid.values.len()];
+        for carve in [true, false] {
+            for shape in shapes.iter().filter(|s| s.carves() == carve) {
+                let (lo, hi) = shape.bounds();
+                let [x0, y0, z0] = grid.voxel_at(lo - margin);
+                let [x1, y1, z1] = grid.voxel_at(hi + margin);
+                for z in z0..=z1 {
+                    for y in y0..=y1 {
+                        for x in x0..=x1 {
+                            let i = grid.index(x, y, z);
+                            let p = grid.position(x, y, z);
+                            if noise[i].is_nan() {
+                                noise[i] = settings.noise.sample(FVector {
+                                    x: p.x,
+                                    y: p.y,
+                                    z: p.z,
+                                });
+                            }
+                            let d = shape.distance(p, noise[i]);
+                            let value = &mut grid.values[i];
+                            *value = if carve { value.min(d) } else { value.max(-d) };
+                        }
+                    }
+                }
+            }
+        }
+        grid
+    }
+
+    pub fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        x + self.dims[0] * (y + self.dims[1] * z)
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> f32 {
+        self.values[self.index(x, y, z)]
+    }
+
+    /// World position of a voxel.
+    pub fn position(&self, x: usize, y: usize, z: usize) -> Vector3<f32> {
+        self.origin + vec3(x as f32, y as f32, z as f32) * self.voxel_size
+    }
+
+    /// Nearest voxel to `pos`, clamped to the grid.
+    pub fn voxel_at(&self, pos: Vector3<f32>) -> [usize; 3] {
+        let local = (pos - self.origin) / self.voxel_size;
+        let mut voxel = [0; 3];
+        for (axis, v) in [local.x, local.y, local.z].into_iter().enumerate() {
+            voxel[axis] = (v.round().max(0.0) as usize).min(self.dims[axis].saturating_sub(1));
+        }
+        voxel
+    }
+
+    /// Trilinearly interpolated value at `pos`. Everything outside the grid is rock.
+    pub fn sample(&self, pos: Vector3<f32>) -> f32 {
+        let local = (pos - self.origin) / self.voxel_size;
+        let local = [local.x, local.y, local.z];
+        if (0..3)
+            .any(|a| self.dims[a] < 2 || local[a] < 0.0 || local[a] > (self.dims[a] - 1) as f32)
+        {
+            return self.voxel_size;
+        }
+        let base = local.map(|v| v.floor() as usize);
+        let base = [0, 1, 2].map(|a| base[a].min(self.dims[a] - 2));
+        let t = [0, 1, 2].map(|a| local[a] - base[a] as f32);
+
+        let mut value = 0.0;
+        for corner in 0..8 {
+            let offset = [corner & 1, (corner >> 1) & 1, (corner >> 2) & 1];
+            let weight = (0..3)
+                .map(|a| if offset[a] == 1 { t[a] } else { 1.0 - t[a] })
+                .product::<f32>();
+            value += weight
+                * self.get(
+                    base[0] + offset[0],
+                    base[1] + offset[1],
+                    base[2] + offset[2],
+                );
+        }
+        value
+    }
+
+    /// Whether `pos` is in carved out space.
+    pub fn is_open(&self, pos: Vector3<f32>) -> bool {
+        self.sample(pos) < 0.0
+    }
+}
+
+/// A straight walkable tunnel along X at the given Y, shared by the analysis tests.
+#[cfg(test)]
+pub(crate) fn tunnel(y: f32, x0: f32, x1: f32) -> RoomFeature {
+    let point = |x| FRoomLinePoint {
+        location: FVector { x, y, z: 0.0 },
+        h_range: 500.0,
+        v_range: 400.0,
+        floor_depth: 100.0,
+        ..Default::default()
+    };
+    RoomFeature::FloodFillLine(FloodFillLine {
+        points: vec![point(x0), point(x1)],
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rma::FRandLinePoint;
+
+    use super::*;
+
+    #[test]
+    fn test_carve_line_and_pillar() {
+        let line = FloodFillLine {
+            points: vec![
+                FRoomLinePoint {
+                    location: FVector::default(),
+                    h_range: 500.0,
+                    v_range: 400.0,
+                    ..Default::default()
+                },
+                FRoomLinePoint {
+                    location: FVector {
+                        x: 2000.0,
+                        y: 0.0,
+                        z: 0.0,
+                    },
+                    h_range: 500.0,
+                    v_range: 400.0,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let pillar_point = |x| FRandLinePoint {
+            location: FVector { x, y: 0.0, z: 0.0 },
+            range: FRandRange {
+                min: 150.0,
+                max: 150.0,
+            },
+            ..Default::default()
+        };
+        let pillar = FloodFillPillar {
+            points: vec![pillar_point(1000.0), pillar_point(1000.1)],
+            ..Default::default()
+        };
+        let room = RoomGenerator {
+            room_features: vec![
+                RoomFeature::FloodFillLine(line),
+                RoomFeature::FloodFillPillar(pillar),
+            ],
+            ..Default::default()
+        };
+        let grid = DensityGrid::carve(&room, &CarveSettings::default());
+
+        assert!(grid.is_open(vec3(0.0, 0.0, 200.0)));
+        assert!(grid.is_open(vec3(1500.0, 300.0, 100.0)));
+        assert!(!grid.is_open(vec3(1000.0, 0.0, 100.0)));
+        assert!(!grid.is_open(vec3(1000.0, 0.0, 1000.0)));
+        assert!(!grid.is_open(vec3(1000.0, 0.0, -300.0)));
+        assert!(!grid.is_open(vec3(10000.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    #[should_panic(expected = "voxel size must be positive")]
+    fn test_carve_zero_voxel_size() {
+        let room = RoomGenerator {
+            room_features: vec![tunnel(0.0, 0.0, 2000.0)],
+            ..Default::default()
+        };
+        DensityGrid::carve(
+            &room,
+            &CarveSettings {
+                voxel_size: 0.0,
+                ..Default::default()
+            },
+        );
+    }
+}
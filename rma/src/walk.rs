@@ -0,0 +1,398 @@
+//! Whether a dwarf can walk between a room's entrances and drop pod locations, found on the
+//! carved volume.
+//!
+//! The open space of every column of a [`DensityGrid`] is split into spans from floor to ceiling,
+//! and each span tall enough for a dwarf is a cell to stand in. Cells of neighbouring columns are
+//! joined when the floor rises no more than a step or the steepest walkable slope allows and the
+//! opening between them is still tall enough. Jumping, climbing and digging are not considered,
+//! so a room passing the check can be crossed on foot.
+
+use std::{cmp::Ordering, collections::BinaryHeap, ops::Range};
+
+use serde::Deserialize;
+use three_d_asset::{vec3, InnerSpace, Vector3};
+
+use crate::{
+    rma::{iter_features, FeaturePath, RoomFeature, RoomGenerator},
+    voxel::DensityGrid,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct WalkSettings {
+    /// Open space a dwarf needs above the floor.
+    pub height: f32,
+    /// Highest ledge a dwarf walks up without jumping.
+    pub step: f32,
+    /// Steepest walkable slope in degrees.
+    pub max_slope: f32,
+}
+
+impl Default for WalkSettings {
+    fn default() -> Self {
+        Self {
+            height: 180.0,
+            step: 50.0,
+            max_slope: 45.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnchorKind {
+    Entrance,
+    DropPod,
+}
+
+impl AnchorKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            AnchorKind::Entrance => "entrance",
+            AnchorKind::DropPod => "drop pod location",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reach {
+    /// Walkable from the root along `points`, `distance` units long.
+    Connected {
+        points: Vec<Vector3<f32>>,
+        distance: f32,
+    },
+    /// Standing on floor cut off from the root.
+    Disconnected,
+    /// Not standing on any floor with room for a dwarf.
+    NoFloor,
+}
+
+/// How an entrance or drop pod location is reached from the root.
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub path: FeaturePath,
+    pub kind: AnchorKind,
+    pub location: Vector3<f32>,
+    pub reach: Reach,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Connectivity {
+    /// Index of the route everything is walked to from: the first entrance, or drop pod location
+    /// if there is none, of the largest group of locations connected to each other.
+    pub root: Option<usize>,
+    /// Every entrance and drop pod location, parents first.
+    pub routes: Vec<Route>,
+}
+
+/// Open span of a column with room for a dwarf.
+struct Cell {
+    column: [usize; 2],
+    floor: f32,
+    ceiling: f32,
+}
+
+struct WalkGraph<'a> {
+    grid: &'a DensityGrid,
+    cells: Vec<Cell>,
+    /// Cells of each column, x varying fastest.
+    columns: Vec<Range<usize>>,
+    /// Cells walkable from each cell, with the distance to them.
+    edges: Vec<Vec<(usize, f32)>>,
+}
+
+impl<'a> WalkGraph<'a> {
+    fn new(grid: &'a DensityGrid, settings: &WalkSettings) -> Self {
+        let [nx, ny, _] = grid.dims;
+        let mut cells = vec![];
+        let mut columns = Vec::with_capacity(nx * ny);
+        for y in 0..ny {
+            for x in 0..nx {
+                let p = grid.position(x, y, 0);
+                let start = cells.len();
+                cells.extend(
+                    grid.clearance(p.x, p.y)
+                        .into_iter()
+                        .filter(|span| span.end - span.start >= settings.height)
+                        .map(|span| Cell {
+                            column: [x, y],
+                            floor: span.start,
+                            ceiling: span.end,
+                        }),
+                );
+                columns.push(start..cells.len());
+            }
+        }
+
+        let slope = settings.max_slope.to_radians().tan();
+        let mut edges = vec![vec![]; cells.len()];
+        for (i, cell) in cells.iter().enumerate() {
+            let [x, y] = cell.column;
+            for (dx, dy) in [(1, 0), (0, 1), (1, 1), (1, -1)] {
+                let (Some(ox), Some(oy)) = (x.checked_add_signed(dx), y.checked_add_signed(dy))
+                else {
+                    continue;
+                };
+                if ox >= nx || oy >= ny {
+                    continue;
+                }
+                let run = grid.voxel_size * ((dx * dx + dy * dy) as f32).sqrt();
+                let rise = settings.step.max(run * slope);
+                for j in columns[ox + nx * oy].clone() {
+                    let other = &cells[j];
+                    let climb = (other.floor - cell.floor).abs();
+                    let opening = cell.ceiling.min(other.ceiling) - cell.floor.max(other.floor);
+                    if climb <= rise && opening >= settings.height {
+                        let distance = (run * run + climb * climb).sqrt();
+                        edges[i].push((j, distance));
+                        edges[j].push((i, distance));
+                    }
+                }
+            }
+        }
+        Self {
+            grid,
+            cells,
+            columns,
+            edges,
+        }
+    }
+
+    /// Where a dwarf stands in `cell`.
+    fn point(&self, cell: usize) -> Vector3<f32> {
+        let Cell { column, floor, .. } = self.cells[cell];
+        let p = self.grid.position(column[0], column[1], 0);
+        vec3(p.x, p.y, floor)
+    }
+
+    /// Cell a dwarf at `location` stands in, allowing it to be up to a voxel below the floor.
+    fn cell_at(&self, location: Vector3<f32>) -> Option<usize> {
+        let [x, y, _] = self.grid.voxel_at(location);
+        self.columns[x + self.grid.dims[0] * y]
+            .clone()
+            .rev()
+            .find(|&i| {
+                let cell = &self.cells[i];
+                cell.floor - self.grid.voxel_size <= location.z && location.z <= cell.ceiling
+            })
+    }
+
+    /// Label of the group of cells connected to each other for every cell.
+    fn components(&self) -> Vec<usize> {
+        let mut labels = vec![usize::MAX; self.cells.len()];
+        let mut stack = vec![];
+        for start in 0..self.cells.len() {
+            if labels[start] != usize::MAX {
+                continue;
+            }
+            labels[start] = start;
+            stack.push(start);
+            while let Some(i) = stack.pop() {
+                for &(j, _) in &self.edges[i] {
+                    if labels[j] == usize::MAX {
+                        labels[j] = start;
+                        stack.push(j);
+                    }
+                }
+            }
+        }
+        labels
+    }
+
+    /// Previous cell on the shortest walk from `from` to every cell, with its length.
+    fn shortest(&self, from: usize) -> (Vec<Option<usize>>, Vec<f32>) {
+        let mut previous = vec![None; self.cells.len()];
+        let mut distances = vec![f32::INFINITY; self.cells.len()];
+        let mut queue = BinaryHeap::new();
+        distances[from] = 0.0;
+        queue.push(Visit(0.0, from));
+        while let Some(Visit(distance, i)) = queue.pop() {
+            if distance > distances[i] {
+                continue;
+            }
+            for &(j, step) in &self.edges[i] {
+                let d = distance + step;
+                if d < distances[j] {
+                    distances[j] = d;
+                    previous[j] = Some(i);
+                    queue.push(Visit(d, j));
+                }
+            }
+        }
+        (previous, distances)
+    }
+}
+
+/// Cell waiting in the queue of [`WalkGraph::shortest`], nearest first.
+struct Visit(f32, usize);
+
+impl PartialEq for Visit {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Visit {}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
+
+/// Walk from the root to every entrance and drop pod location in `room`. Pass an instantiated
+/// room and the grid carved from it to check a single variant.
+pub fn analyze(room: &RoomGenerator, grid: &DensityGrid, settings: &WalkSettings) -> Connectivity {
+    let mut anchors = vec![];
+    iter_features(&room.room_features, &mut vec![], &mut |feature, path| {
+        let (kind, location) = match feature {
+            RoomFeature::EntranceFeature(f) => (AnchorKind::Entrance, f.location),
+            RoomFeature::DropPodCalldownLocationFeature(f) => (AnchorKind::DropPod, f.location),
+            _ => return,
+        };
+        anchors.push((path.to_vec(), kind, Vector3::from(location)));
+    });
+    if anchors.is_empty() || grid.values.is_empty() {
+        return Connectivity {
+            root: None,
+            routes: anchors
+                .into_iter()
+                .map(|(path, kind, location)| Route {
+                    path,
+                    kind,
+                    location,
+                    reach: Reach::NoFloor,
+                })
+                .collect(),
+        };
+    }
+
+    let graph = WalkGraph::new(grid, settings);
+    let labels = graph.components();
+    let cells = anchors
+        .iter()
+        .map(|(_, _, location)| graph.cell_at(*location))
+        .collect::<Vec<_>>();
+    let group_size = |cell: usize| {
+        cells
+            .iter()
+            .flatten()
+            .filter(|&&c| labels[c] == labels[cell])
+            .count()
+    };
+    // earlier entrances win ties, so the root stays put while a room is edited
+    let root = (0..anchors.len())
+        .filter(|&i| cells[i].is_some())
+        .max_by_key(|&i| {
+            (
+                cells[i].map(group_size),
+                anchors[i].1 == AnchorKind::Entrance,
+                std::cmp::Reverse(i),
+            )
+        });
+
+    // the root's walk, with where it starts
+    let start = root.and_then(|root| Some((anchors[root].2, cells[root]?)));
+    let walks = start.map(|(_, cell)| graph.shortest(cell));
+    let routes = anchors
+        .into_iter()
+        .zip(cells)
+        .map(|((path, kind, location), cell)| {
+            let reach = match (cell, start, &walks) {
+                (None, _, _) => Reach::NoFloor,
+                (Some(cell), Some((from, root_cell)), Some((previous, distances)))
+                    if labels[cell] == labels[root_cell] =>
+                {
+                    let mut points = vec![location];
+                    let mut at = Some(cell);
+                    while let Some(i) = at {
+                        points.push(graph.point(i));
+                        at = previous[i];
+                    }
+                    points.push(from);
+                    points.reverse();
+                    let distance = distances[cell]
+                        + (location - graph.point(cell)).magnitude()
+                        + (from - graph.point(root_cell)).magnitude();
+                    Reach::Connected { points, distance }
+                }
+                _ => Reach::Disconnected,
+            };
+            Route {
+                path,
+                kind,
+                location,
+                reach,
+            }
+        })
+        .collect();
+    Connectivity { root, routes }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        rma::{DropPodCalldownLocationFeature, EntranceFeature, FVector},
+        voxel::{tunnel, CarveSettings},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_walk_between_entrances() {
+        let entrance = |x, y| {
+            RoomFeature::EntranceFeature(EntranceFeature {
+                location: FVector { x, y, z: 0.0 },
+                ..Default::default()
+            })
+        };
+        let room = RoomGenerator {
+            room_features: vec![
+                tunnel(0.0, 0.0, 2000.0),
+                tunnel(3000.0, 0.0, 1000.0),
+                entrance(0.0, 0.0),
+                entrance(500.0, 3000.0),
+                RoomFeature::DropPodCalldownLocationFeature(DropPodCalldownLocationFeature {
+                    location: FVector {
+                        x: 2000.0,
+                        y: 0.0,
+                        z: -100.0,
+                    },
+                    ..Default::default()
+                }),
+                entrance(1000.0, 1500.0),
+            ],
+            ..Default::default()
+        };
+        let grid = DensityGrid::carve(&room, &CarveSettings::default());
+
+        let connectivity = analyze(&room, &grid, &WalkSettings::default());
+        assert_eq!(connectivity.root, Some(0));
+        let reach = |i: usize| &connectivity.routes[i].reach;
+        assert_eq!(reach(1), &Reach::Disconnected);
+        assert_eq!(reach(3), &Reach::NoFloor);
+        let Reach::Connected { points, distance } = reach(2) else {
+            panic!("{:?}", reach(2));
+        };
+        assert_eq!(points[0], vec3(0.0, 0.0, 0.0));
+        assert_eq!(*points.last().unwrap(), vec3(2000.0, 0.0, -100.0));
+        assert!((distance - 2000.0).abs() < 300.0, "{distance}");
+
+        // too tall to fit through the tunnel
+        let tall = WalkSettings {
+            height: 1000.0,
+            ..Default::default()
+        };
+        let connectivity = analyze(&room, &grid, &tall);
+        assert_eq!(connectivity.root, None);
+        assert!(connectivity
+            .routes
+            .iter()
+            .all(|r| r.reach == Reach::NoFloor));
+    }
+}
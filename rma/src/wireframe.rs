@@ -0,0 +1,691 @@
+//! Line segment outlines of features, shared by the viewer and the exporters.
+
+use three_d_asset::{vec2, vec3, InnerSpace, Quat, Vector3, VectorSpace};
+
+use crate::{
+    bounds::Extent,
+    rma::{
+        iter_features, EItemAdjustmentType, EntranceFeature, FRandRange, FVector, FloodFillBox,
+        FloodFillLine, FloodFillPillar, RandomSelector, RoomFeature, RoomGeneratorBase,
+        SpawnActorFeature, SpawnTriggerFeature,
+    },
+    voxel::LineProfile,
+};
+
+pub type Segment = [Vector3<f32>; 2];
+
+pub trait Wireframe {
+    fn wireframe(&self) -> Vec<Segment>;
+}
+
+/// Segments approximating an elliptical arc around `center` spanned by the axes `a` and `b`,
+/// from angle 0 to `end` (in turns).
+fn arc(
+    segments: &mut Vec<Segment>,
+    center: Vector3<f32>,
+    a: Vector3<f32>,
+    b: Vector3<f32>,
+    end: f32,
+) {
+    let count = (40.0 * end) as usize;
+    let point = |i: usize| {
+        let angle = 2.0 * std::f32::consts::PI * end * i as f32 / count as f32;
+        center + a * angle.cos() + b * angle.sin()
+    };
+    segments.extend((0..count).map(|i| [point(i), point(i + 1)]));
+}
+
+/// Arc of `radius` around `center` in the plane of the unit axes `a` and `b`, from `-angle` to
+/// `angle` degrees off `a`, with spokes at both ends.
+fn fan(
+    segments: &mut Vec<Segment>,
+    center: Vector3<f32>,
+    a: Vector3<f32>,
+    b: Vector3<f32>,
+    angle: f32,
+    radius: f32,
+) {
+    let (sin, cos) = (-angle).to_radians().sin_cos();
+    let start = (a * cos + b * sin) * radius;
+    let start_b = (b * cos - a * sin) * radius;
+    arc(segments, center, start, start_b, angle / 180.0);
+    let (sin, cos) = angle.to_radians().sin_cos();
+    segments.push([center, center + start]);
+    segments.push([center, center + (a * cos + b * sin) * radius]);
+}
+
+/// Components of a scale, with those missing from the asset defaulting to one.
+pub(crate) fn scale_or_one(scale: FVector) -> Vector3<f32> {
+    let or_one = |v: f32| if v == 0.0 { 1.0 } else { v };
+    vec3(or_one(scale.x), or_one(scale.y), or_one(scale.z))
+}
+
+/// Number of points in a line's cross-section outline.
+pub(crate) const SECTION_STEPS: usize = 48;
+
+impl FloodFillLine {
+    /// Horizontal axis pointing to the left of the line at each point, averaged over the
+    /// adjacent segments.
+    pub(crate) fn sides(&self) -> Vec<Vector3<f32>> {
+        let location = |i: usize| Vector3::from(self.points[i].location);
+        let last = self.points.len().saturating_sub(1);
+        (0..self.points.len())
+            .map(|i| {
+                let d = location((i + 1).min(last)) - location(i.saturating_sub(1));
+                let d = vec2(d.x, d.y);
+                if d.magnitude2() > 0.0 {
+                    let d = d.normalize();
+                    vec3(-d.y, d.x, 0.0)
+                } else {
+                    vec3(0.0, 1.0, 0.0)
+                }
+            })
+            .collect()
+    }
+
+    /// Cross-sections at every point with the noise fixed at `noise`, joined by `rails` evenly
+    /// spaced lines running along the line.
+    fn sections(&self, noise: f32, rails: usize) -> Vec<Segment> {
+        let mut segments = Vec::new();
+        let sections = self
+            .points
+            .iter()
+            .zip(self.sides())
+            .map(|(point, side)| {
+                let location = Vector3::from(point.location);
+                point
+                    .profile()
+                    .outline(noise, SECTION_STEPS)
+                    .into_iter()
+                    .map(|p| location + side * p.x + vec3(0.0, 0.0, p.y))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        for section in &sections {
+            segments
+                .extend((0..section.len()).map(|i| [section[i], section[(i + 1) % section.len()]]));
+        }
+        for pair in sections.windows(2) {
+            segments.extend(
+                (0..rails)
+                    .map(|i| i * SECTION_STEPS / rails)
+                    .map(|i| [pair[0][i], pair[1][i]]),
+            );
+        }
+        segments
+    }
+
+    /// Inner and outer extent of the line's noise, as cross-sections at every point.
+    pub fn noise_wireframe(&self) -> Vec<Segment> {
+        let has_noise = self.points.iter().any(|p| {
+            p.wall_noise_range != 0.0 || p.cieling_noise_range != 0.0 || p.floor_noise_range != 0.0
+        });
+        if !has_noise {
+            return vec![];
+        }
+        let mut segments = self.sections(-1.0, 4);
+        segments.extend(self.sections(1.0, 4));
+        segments
+    }
+}
+
+impl Wireframe for FloodFillLine {
+    /// Cross-section of the carved tunnel at every point, showing the vault or flat ceiling and
+    /// the tilted floor, plus the horizontal reach around each point.
+    fn wireframe(&self) -> Vec<Segment> {
+        let mut segments = self.sections(0.0, 8);
+        for point in &self.points {
+            let center = point.location.into();
+            let (x, y) = (vec3(point.h_range, 0.0, 0.0), vec3(0.0, point.h_range, 0.0));
+            // horizontal perimeter circle
+            arc(&mut segments, center, x, y, 1.0);
+        }
+        segments
+    }
+}
+
+/// Bounds of a range, treating an unset range as `default`.
+pub(crate) fn range_or(range: &FRandRange, default: f32) -> (f32, f32) {
+    if range.min == 0.0 && range.max == 0.0 {
+        (default, default)
+    } else {
+        (range.min, range.max)
+    }
+}
+
+/// Segments split into `dashes` pieces, keeping every other one.
+pub(crate) fn dash(segments: Vec<Segment>, dashes: usize) -> Vec<Segment> {
+    let t = |k: usize| k as f32 / dashes as f32;
+    segments
+        .into_iter()
+        .flat_map(|[a, b]| {
+            (0..dashes)
+                .step_by(2)
+                .map(move |k| [a.lerp(b, t(k)), a.lerp(b, t(k + 1))])
+        })
+        .collect()
+}
+
+/// Unit vector perpendicular to the unit vector `dir`, crossing it with the axis least aligned
+/// with it so the two are never parallel.
+fn perpendicular(dir: Vector3<f32>) -> Vector3<f32> {
+    let axis = if dir.x.abs() <= dir.y.abs() && dir.x.abs() <= dir.z.abs() {
+        vec3(1.0, 0.0, 0.0)
+    } else if dir.y.abs() <= dir.z.abs() {
+        vec3(0.0, 1.0, 0.0)
+    } else {
+        vec3(0.0, 0.0, 1.0)
+    };
+    dir.cross(axis).normalize()
+}
+
+impl FloodFillPillar {
+    /// Axes perpendicular to the pillar at every point, carried along from point to point to
+    /// avoid twisting. The second axis is the pillar's direction crossed with the first.
+    pub(crate) fn frames(&self) -> Vec<(Vector3<f32>, Vector3<f32>)> {
+        let location = |i: usize| Vector3::from(self.points[i].location);
+        let last = self.points.len().saturating_sub(1);
+        let mut frames = vec![];
+        let mut u = vec3(1.0, 0.0, 0.0);
+        for i in 0..self.points.len() {
+            let dir = location((i + 1).min(last)) - location(i.saturating_sub(1));
+            let dir = if dir.magnitude2() > 0.0 {
+                dir.normalize()
+            } else {
+                vec3(0.0, 0.0, 1.0)
+            };
+            let projected = u - dir * u.dot(dir);
+            u = if projected.magnitude2() > 1e-6 {
+                projected.normalize()
+            } else {
+                perpendicular(dir)
+            };
+            frames.push((u, dir.cross(u)));
+        }
+        frames
+    }
+
+    /// Smallest and largest radius at point `i`, scaled by `range_scale` and by `endcap_scale`
+    /// at the first and last point.
+    pub fn radius_range(&self, i: usize) -> (f32, f32) {
+        let range_scale = range_or(&self.range_scale, 1.0);
+        let endcap_scale = if i == 0 || i + 1 == self.points.len() {
+            range_or(&self.endcap_scale, 1.0)
+        } else {
+            (1.0, 1.0)
+        };
+        let range = &self.points[i].range;
+        (
+            range.min * range_scale.0 * endcap_scale.0,
+            range.max * range_scale.1 * endcap_scale.1,
+        )
+    }
+
+    /// Largest noise displacement at point `i`, scaled by `noise_range_scale`.
+    pub fn max_noise(&self, i: usize) -> f32 {
+        self.points[i].noise_range.max * range_or(&self.noise_range_scale, 1.0).1
+    }
+
+    /// Dashed shell around the largest radius plus the largest noise.
+    pub fn noise_wireframe(&self) -> Vec<Segment> {
+        if (0..self.points.len()).all(|i| self.max_noise(i) == 0.0) {
+            return vec![];
+        }
+        let frames = self.frames();
+        let mut segments = vec![];
+        let shell = |i: usize| self.radius_range(i).1 + self.max_noise(i);
+        for (i, (point, &(u, v))) in self.points.iter().zip(&frames).enumerate() {
+            let mut ring = vec![];
+            arc(
+                &mut ring,
+                point.location.into(),
+                u * shell(i),
+                v * shell(i),
+                1.0,
+            );
+            segments.extend(dash(ring, 2));
+        }
+        for (i, pair) in self.points.windows(2).enumerate() {
+            let ((u0, v0), (u1, v1)) = (frames[i], frames[i + 1]);
+            let (a, b) = (
+                Vector3::from(pair[0].location),
+                Vector3::from(pair[1].location),
+            );
+            let rails = [(u0, u1), (v0, v1), (-u0, -u1), (-v0, -v1)]
+                .map(|(d0, d1)| [a + d0 * shell(i), b + d1 * shell(i + 1)]);
+            segments.extend(dash(rails.to_vec(), 8));
+        }
+        segments
+    }
+}
+
+impl Wireframe for FloodFillPillar {
+    /// Centerline with rings at the smallest and largest radius of every point. The largest
+    /// `fill_amount` is drawn as an arc outside the rings, starting from the first axis and
+    /// spanning that fraction of a turn, and the largest `skew_factor` as a spoke along the
+    /// first axis, that long relative to the largest radius. Smaller values get a tick.
+    fn wireframe(&self) -> Vec<Segment> {
+        let mut segments: Vec<Segment> = self
+            .points
+            .windows(2)
+            .map(|pair| [pair[0].location.into(), pair[1].location.into()])
+            .collect();
+        for (i, (point, (u, v))) in self.points.iter().zip(self.frames()).enumerate() {
+            let center = Vector3::from(point.location);
+            let (min, max) = self.radius_range(i);
+            arc(&mut segments, center, u * min, v * min, 1.0);
+            arc(&mut segments, center, u * max, v * max, 1.0);
+
+            let tick = |at: Vector3<f32>, dir: Vector3<f32>| [at - dir * 0.05, at + dir * 0.05];
+            let fill = &point.fill_amount;
+            if fill.max > 0.0 {
+                let r = max * 1.15;
+                arc(&mut segments, center, u * r, v * r, fill.max.min(1.0));
+                let angle = 2.0 * std::f32::consts::PI * fill.min.clamp(0.0, 1.0);
+                let dir = u * angle.cos() + v * angle.sin();
+                segments.push(tick(center + dir * r, dir * r));
+            }
+            let skew = &point.skew_factor;
+            if skew.max != 0.0 {
+                segments.push([center, center + u * (max * skew.max)]);
+                segments.push(tick(center + u * (max * skew.min), v * max));
+            }
+        }
+        segments
+    }
+}
+
+impl FloodFillBox {
+    /// Corners of the rotated box grown by `grow` on every side, indexed by the bits of their
+    /// signs along X, Y and Z.
+    pub fn corners(&self, grow: f32) -> [Vector3<f32>; 8] {
+        let e = Vector3::from(self.extends) + vec3(grow, grow, grow);
+        box_corners(self.position.into(), self.rotation.into(), e)
+    }
+
+    /// Dashed box grown by the noise range.
+    pub fn noise_wireframe(&self) -> Vec<Segment> {
+        if self.noise_range == 0.0 {
+            return vec![];
+        }
+        dash(box_edges(&self.corners(self.noise_range.abs())), 8)
+    }
+}
+
+/// Corners of a box rotated by `rotation` with half extents `e`, indexed by the bits of their
+/// signs along X, Y and Z.
+fn box_corners(center: Vector3<f32>, rotation: Quat, e: Vector3<f32>) -> [Vector3<f32>; 8] {
+    [0, 1, 2, 3, 4, 5, 6, 7].map(|i| {
+        let sign = |bit: usize| if i & bit != 0 { 1.0 } else { -1.0 };
+        center + rotation * vec3(sign(1) * e.x, sign(2) * e.y, sign(4) * e.z)
+    })
+}
+
+/// Every pair of corners differing in exactly one axis.
+fn box_edges(corners: &[Vector3<f32>; 8]) -> Vec<Segment> {
+    (0..8)
+        .flat_map(|i| [1, 2, 4].map(|bit| (i, i | bit)))
+        .filter(|(i, j)| i != j)
+        .map(|(i, j)| [corners[i], corners[j]])
+        .collect()
+}
+
+impl Wireframe for FloodFillBox {
+    /// Edges of the rotated box. Boxes filling in rock rather than carving also get crosses on
+    /// their faces.
+    fn wireframe(&self) -> Vec<Segment> {
+        let corners = self.corners(0.0);
+        let mut segments = box_edges(&corners);
+        if !self.is_carver {
+            for bit in [1, 2, 4] {
+                // the two faces perpendicular to `bit`, as corners in order around the face
+                let (b, c) = match bit {
+                    1 => (2, 4),
+                    2 => (4, 1),
+                    _ => (1, 2),
+                };
+                for side in [0, bit] {
+                    let face = [side, side | b, side | b | c, side | c];
+                    segments.push([corners[face[0]], corners[face[2]]]);
+                    segments.push([corners[face[1]], corners[face[3]]]);
+                }
+            }
+        }
+        segments
+    }
+}
+
+/// Approximate cross-section of the tunnels generated between rooms, drawn at entrances.
+pub const TUNNEL_MOUTH: LineProfile = LineProfile {
+    width: 400.0,
+    height: 450.0,
+    ceiling: None,
+    floor_depth: 50.0,
+    floor_slope: 0.0,
+    wall_noise: 0.0,
+    ceiling_noise: 0.0,
+    floor_noise: 0.0,
+};
+
+/// Length of the arrow drawn along an entrance's direction.
+const ENTRANCE_ARROW: f32 = 600.0;
+
+impl Wireframe for EntranceFeature {
+    /// Arrow along the direction and the tunnel mouth facing it.
+    fn wireframe(&self) -> Vec<Segment> {
+        let location = Vector3::from(self.location);
+        let rotation: Quat = self.direction.into();
+        let forward = rotation * vec3(1.0, 0.0, 0.0);
+        let side = rotation * vec3(0.0, 1.0, 0.0);
+        let up = rotation * vec3(0.0, 0.0, 1.0);
+
+        let tip = location + forward * ENTRANCE_ARROW;
+        let head = tip - forward * (ENTRANCE_ARROW / 4.0);
+        let mut segments = vec![[location, tip]];
+        for d in [side, -side, up, -up] {
+            segments.push([tip, head + d * (ENTRANCE_ARROW / 8.0)]);
+        }
+
+        let mouth = TUNNEL_MOUTH
+            .outline(0.0, SECTION_STEPS)
+            .into_iter()
+            .map(|p| location + side * p.x + up * p.y)
+            .collect::<Vec<_>>();
+        segments.extend((0..mouth.len()).map(|i| [mouth[i], mouth[(i + 1) % mouth.len()]]));
+        segments
+    }
+}
+
+/// Length of the ray drawn along a spawn's adjustment direction.
+pub const ADJUSTMENT_RAY: f32 = 500.0;
+/// Half size of the plate drawn at the end of an adjustment ray.
+const SNAP_PLATE: f32 = 80.0;
+/// Radius of the fans showing a spawn's rotation delta.
+const ROTATION_FAN: f32 = 200.0;
+
+impl SpawnActorFeature {
+    /// Unit direction the spawn is moved in to snap onto terrain, if it is adjusted at all.
+    pub fn adjustment_ray(&self) -> Option<Vector3<f32>> {
+        let direction = Vector3::from(self.adjustment_direction);
+        if matches!(self.adjustment, EItemAdjustmentType::None) || direction.magnitude2() == 0.0 {
+            return None;
+        }
+        Some(direction.normalize())
+    }
+
+    /// Smallest and largest scale of the spawned actor.
+    pub fn scale_range(&self) -> (Vector3<f32>, Vector3<f32>) {
+        (scale_or_one(self.scale_min), scale_or_one(self.scale_max))
+    }
+}
+
+impl Wireframe for SpawnActorFeature {
+    /// Adjustment ray ending in a plate facing the surface it snaps to, and fans for the yaw,
+    /// pitch and roll deltas.
+    fn wireframe(&self) -> Vec<Segment> {
+        let location = Vector3::from(self.location);
+        let mut segments = vec![];
+        if let Some(direction) = self.adjustment_ray() {
+            let tip = location + direction * ADJUSTMENT_RAY;
+            segments.push([location, tip]);
+            // floors and ceilings are horizontal, walls face back along the ray
+            let normal = match self.adjustment {
+                EItemAdjustmentType::Wall if vec2(direction.x, direction.y).magnitude2() > 0.0 => {
+                    vec3(direction.x, direction.y, 0.0).normalize()
+                }
+                EItemAdjustmentType::Wall => vec3(1.0, 0.0, 0.0),
+                _ => vec3(0.0, 0.0, 1.0),
+            };
+            let a = if normal.z.abs() > 0.5 {
+                vec3(1.0, 0.0, 0.0)
+            } else {
+                normal.cross(vec3(0.0, 0.0, 1.0)).normalize()
+            };
+            let (a, b) = (a * SNAP_PLATE, normal.cross(a) * SNAP_PLATE);
+            let plate = [tip + a + b, tip - a + b, tip - a - b, tip + a - b];
+            segments.extend((0..4).map(|i| [plate[i], plate[(i + 1) % 4]]));
+            segments.push([plate[0], plate[2]]);
+            segments.push([plate[1], plate[3]]);
+        }
+
+        let (x, y, z) = (
+            vec3(1.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            vec3(0.0, 0.0, 1.0),
+        );
+        let delta = self.rotation_delta;
+        for (angle, a, b) in [(delta.yaw, x, y), (delta.pitch, x, z), (delta.roll, y, z)] {
+            if angle.abs() > 0.0 {
+                fan(
+                    &mut segments,
+                    location,
+                    a,
+                    b,
+                    angle.abs().min(180.0),
+                    ROTATION_FAN,
+                );
+            }
+        }
+        segments
+    }
+}
+
+/// Half size of a spawn trigger before scaling. The trigger class is not imported, so this
+/// assumes a one meter cube like UE's default cube brush.
+pub const TRIGGER_EXTENT: f32 = 50.0;
+
+impl SpawnTriggerFeature {
+    /// `Scale3D` of the transform.
+    pub fn scale(&self) -> Vector3<f32> {
+        scale_or_one(self.transform.Scale3D)
+    }
+
+    /// Corners of the trigger volume.
+    pub fn corners(&self) -> [Vector3<f32>; 8] {
+        box_corners(
+            self.transform.translation.into(),
+            self.transform.rotation.into(),
+            self.scale() * TRIGGER_EXTENT,
+        )
+    }
+}
+
+impl Wireframe for SpawnTriggerFeature {
+    fn wireframe(&self) -> Vec<Segment> {
+        box_edges(&self.corners())
+    }
+}
+
+/// Space left between a random selector's group box and its children.
+const GROUP_MARGIN: f32 = 100.0;
+
+impl RandomSelector {
+    /// Dashed box around everything the selector may choose from.
+    pub fn group_wireframe(&self) -> Vec<Segment> {
+        let mut extent: Option<Extent> = None;
+        iter_features(&self.base.room_features, &mut vec![], &mut |feature, _| {
+            if let Some(e) = feature.extent() {
+                extent = Some(extent.map_or(e, |x| x.union(e)));
+            }
+        });
+        let Some(extent) = extent else {
+            return vec![];
+        };
+        let margin = vec3(GROUP_MARGIN, GROUP_MARGIN, GROUP_MARGIN);
+        let group = Extent {
+            min: extent.min - margin,
+            max: extent.max + margin,
+        };
+        dash(group.wireframe(), 16)
+    }
+}
+
+impl Wireframe for RoomFeature {
+    /// Outline of the feature, empty for feature types without one. Random selectors are not
+    /// outlined, see [`RandomSelector::group_wireframe`].
+    fn wireframe(&self) -> Vec<Segment> {
+        match self {
+            RoomFeature::FloodFillLine(f) => f.wireframe(),
+            RoomFeature::FloodFillPillar(f) => f.wireframe(),
+            RoomFeature::FloodFillBox(f) => f.wireframe(),
+            RoomFeature::EntranceFeature(f) => f.wireframe(),
+            RoomFeature::SpawnTriggerFeature(f) => f.wireframe(),
+            RoomFeature::SpawnActorFeature(f) => f.wireframe(),
+            _ => vec![],
+        }
+    }
+}
+
+impl Wireframe for Extent {
+    fn wireframe(&self) -> Vec<Segment> {
+        box_edges(&self.corners())
+    }
+}
+
+impl Wireframe for RoomGeneratorBase {
+    /// Circles around the bounds sphere in the three axis planes.
+    fn wireframe(&self) -> Vec<Segment> {
+        let mut segments = vec![];
+        let center = vec3(0.0, 0.0, 0.0);
+        let [x, y, z] = [
+            vec3(1.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            vec3(0.0, 0.0, 1.0),
+        ]
+        .map(|axis| axis * self.bounds);
+        for (a, b) in [(x, y), (x, z), (y, z)] {
+            arc(&mut segments, center, a, b, 1.0);
+        }
+        segments
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rma::{FRandLinePoint, RoomFeatureBase};
+
+    use super::*;
+
+    fn close(a: Vector3<f32>, b: Vector3<f32>) -> bool {
+        (a - b).magnitude() < 1e-3
+    }
+
+    #[test]
+    fn test_arc_and_fan() {
+        let center = vec3(100.0, 0.0, 0.0);
+        let (a, b) = (vec3(0.0, 50.0, 0.0), vec3(0.0, 0.0, 50.0));
+        let mut segments = vec![];
+        arc(&mut segments, center, a, b, 1.0);
+        assert_eq!(segments.len(), 40);
+        assert!(segments
+            .iter()
+            .flatten()
+            .all(|&p| ((p - center).magnitude() - 50.0).abs() < 1e-3));
+        assert!(close(segments[0][0], segments[39][1]));
+
+        let mut segments = vec![];
+        fan(
+            &mut segments,
+            center,
+            vec3(1.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            90.0,
+            10.0,
+        );
+        // half circle from -Y to +Y through +X, then the two spokes
+        assert_eq!(segments.len(), 22);
+        assert!(close(segments[0][0], center + vec3(0.0, -10.0, 0.0)));
+        assert!(close(segments[10][0], center + vec3(10.0, 0.0, 0.0)));
+        assert!(close(segments[19][1], center + vec3(0.0, 10.0, 0.0)));
+        assert!(close(segments[20][1], center + vec3(0.0, -10.0, 0.0)));
+        assert!(close(segments[21][1], center + vec3(0.0, 10.0, 0.0)));
+    }
+
+    #[test]
+    fn test_dash() {
+        let dashed = dash(vec![[vec3(0.0, 0.0, 0.0), vec3(16.0, 0.0, 0.0)]], 16);
+        assert_eq!(dashed.len(), 8);
+        for (i, [a, b]) in dashed.into_iter().enumerate() {
+            assert!(close(a, vec3(2.0 * i as f32, 0.0, 0.0)));
+            assert!(close(b, vec3(2.0 * i as f32 + 1.0, 0.0, 0.0)));
+        }
+    }
+
+    #[test]
+    fn test_frames() {
+        let pillar = |locations: &[[f32; 3]]| FloodFillPillar {
+            points: locations
+                .iter()
+                .map(|&[x, y, z]| FRandLinePoint {
+                    location: FVector { x, y, z },
+                    range: FRandRange {
+                        min: 100.0,
+                        max: 200.0,
+                    },
+                    ..Default::default()
+                })
+                .collect(),
+            endcap_scale: FRandRange { min: 2.0, max: 3.0 },
+            ..Default::default()
+        };
+        for locations in [
+            &[[0.0, 0.0, 0.0], [1000.0, 0.0, 0.0]][..],
+            &[[0.0, 0.0, 0.0], [0.0, 1000.0, 0.0]],
+            &[[0.0, 0.0, 0.0], [0.0, 0.0, 1000.0]],
+            &[[0.0, 0.0, 0.0], [0.0, 500.0, 0.0], [500.0, 500.0, 300.0]],
+        ] {
+            let pillar = pillar(locations);
+            let frames = pillar.frames();
+            assert_eq!(frames.len(), locations.len());
+            for (u, v) in frames {
+                assert!((u.magnitude() - 1.0).abs() < 1e-4, "{locations:?}");
+                assert!((v.magnitude() - 1.0).abs() < 1e-4, "{locations:?}");
+                assert!(u.dot(v).abs() < 1e-4, "{locations:?}");
+            }
+        }
+
+        for dir in [
+            vec3(1.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            vec3(0.0, 0.0, -1.0),
+            vec3(1.0, 1.0, 1.0).normalize(),
+        ] {
+            let u = perpendicular(dir);
+            assert!((u.magnitude() - 1.0).abs() < 1e-4 && u.dot(dir).abs() < 1e-4);
+        }
+
+        let pillar = pillar(&[[0.0; 3], [0.0, 500.0, 0.0], [0.0, 1000.0, 0.0]]);
+        assert_eq!(pillar.radius_range(0), (200.0, 600.0));
+        assert_eq!(pillar.radius_range(1), (100.0, 200.0));
+        assert_eq!(pillar.radius_range(2), (200.0, 600.0));
+    }
+
+    #[test]
+    fn test_group_wireframe() {
+        let entrance = |x, y, z| {
+            RoomFeature::EntranceFeature(EntranceFeature {
+                location: FVector { x, y, z },
+                ..Default::default()
+            })
+        };
+        let selector = RandomSelector {
+            base: RoomFeatureBase {
+                room_features: vec![entrance(0.0, 0.0, 0.0), entrance(1000.0, 500.0, 200.0)],
+            },
+            ..Default::default()
+        };
+        let segments = selector.group_wireframe();
+        // every edge of the box in 8 dashes
+        assert_eq!(segments.len(), 12 * 8);
+        let extent = segments
+            .iter()
+            .flatten()
+            .map(|&p| Extent { min: p, max: p })
+            .reduce(Extent::union)
+            .unwrap();
+        assert!(close(extent.min, vec3(-100.0, -100.0, -100.0)));
+        assert!(close(extent.max, vec3(1100.0, 600.0, 300.0)));
+
+        assert!(RandomSelector::default().group_wireframe().is_empty());
+    }
+}
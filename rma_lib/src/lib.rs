@@ -108,7 +108,12 @@ impl<C: Read + Seek, T: FromProperty<C>> FromProperty<C> for Vec<T> {
 
 impl<C: Read + Seek, T: FromProperty<C>> FromProperty<C> for Option<T> {
     fn from_property(asset: &Asset<C>, property: &Property) -> Result<Self> {
-        Ok(Some(T::from_property(asset, property)?))
+        match property {
+            Property::ObjectProperty(obj) if 0 == obj.value.index => Ok(None),
+            // imports live in other packages, which are not loaded
+            Property::ObjectProperty(obj) if obj.value.index < 0 => Ok(None),
+            _ => Ok(Some(T::from_property(asset, property)?)),
+        }
     }
 }
 